@@ -0,0 +1,77 @@
+//! Times generating the completions of a large CLI, run with `cargo bench --all-features`.
+
+use std::time::{Duration, Instant};
+
+use clap_complete_command::Shell;
+
+const RUNS: u32 = 10;
+
+/// A CLI with hundreds of subcommands, each with a few options
+fn command() -> clap::Command {
+    let options = (0..8).map(|option| {
+        let name = leak(format!("option-{option}"));
+        clap::Arg::new(name)
+            .long(name)
+            .help(format!("Option number {option}"))
+    });
+    let options = options.collect::<Vec<_>>();
+    clap::Command::new("bench").subcommands((0..300).map(|index| {
+        clap::Command::new(leak(format!("subcommand-{index}")))
+            .about(format!("Subcommand number {index}"))
+            .args(options.clone())
+    }))
+}
+
+/// Without clap's `string` feature, names have to be `&'static str`s
+fn leak(name: String) -> &'static str {
+    Box::leak(name.into_boxed_str())
+}
+
+/// The average time of `RUNS` runs of `run`
+fn time(name: &str, mut run: impl FnMut()) -> Duration {
+    run();
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        run();
+    }
+    let average = start.elapsed() / RUNS;
+    println!("{name}: {average:?}");
+    average
+}
+
+fn main() {
+    let mut command = command();
+    let out_dir = tempfile::tempdir().unwrap();
+
+    let sequential = time("generate_all_to", || {
+        Shell::generate_all_to(&mut command, out_dir.path()).unwrap();
+    });
+    #[cfg(feature = "parallel")]
+    {
+        let parallel = time("generate_all_to_parallel", || {
+            Shell::generate_all_to_parallel(&command, out_dir.path()).unwrap();
+        });
+        println!(
+            "generate_all_to_parallel is {:.1}x as fast",
+            sequential.as_secs_f64() / parallel.as_secs_f64()
+        );
+    }
+    #[cfg(not(feature = "parallel"))]
+    let _ = sequential;
+
+    for &shell in Shell::all() {
+        let path = out_dir.path().join(shell.file_name("bench"));
+        let unbuffered = time(&format!("{shell} generate"), || {
+            let mut file = std::fs::File::create(&path).unwrap();
+            shell.generate(&mut command, &mut file);
+        });
+        let buffered = time(&format!("{shell} generate_buffered"), || {
+            let mut file = std::fs::File::create(&path).unwrap();
+            shell.generate_buffered(&mut command, &mut file);
+        });
+        println!(
+            "{shell} generate_buffered is {:.2}x as fast",
+            unbuffered.as_secs_f64() / buffered.as_secs_f64()
+        );
+    }
+}
@@ -0,0 +1,26 @@
+//! What a `build.rs` generating completions for packaging looks like, with the CLI defined in a
+//! module shared with the binary, e.g. `include!("src/cli.rs")`.
+//!
+//! Add `clap_complete_command` to `[build-dependencies]` with `default-features = false` and the
+//! shells to package, as the build script only needs the generators.
+
+use clap::Command;
+
+fn build_cli() -> Command {
+    Command::new("cli")
+        .subcommand(Command::new("serve").about("Serve the site"))
+        .subcommand(Command::new("build").about("Build the site"))
+}
+
+fn main() -> Result<(), std::io::Error> {
+    let out_dir = std::env::var_os("OUT_DIR").ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::NotFound, "OUT_DIR is set by cargo")
+    })?;
+    println!("cargo:rerun-if-changed=src/cli.rs");
+
+    let paths = clap_complete_command::Shell::generate_all_to(&mut build_cli(), out_dir)?;
+    for path in paths {
+        println!("cargo:warning=generated {}", path.display());
+    }
+    Ok(())
+}
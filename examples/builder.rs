@@ -1,21 +1,9 @@
-use clap::{Arg, Command};
+use clap::Command;
 
 fn build_cli() -> Command {
     Command::new(env!("CARGO_PKG_NAME"))
         .subcommand_required(true)
-        .subcommand(
-            Command::new("completions")
-                .about("Generate shell completions")
-                .arg(
-                    Arg::new("shell")
-                        .value_name("SHELL")
-                        .help("The shell to generate the completions for")
-                        .required(true)
-                        .value_parser(
-                            clap::builder::EnumValueParser::<clap_complete_command::Shell>::new(),
-                        ),
-                ),
-        )
+        .subcommand(clap_complete_command::completions_subcommand())
 }
 
 fn main() {
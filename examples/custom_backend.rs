@@ -0,0 +1,111 @@
+use std::sync::OnceLock;
+
+use clap::builder::PossibleValue;
+use clap::{Arg, Command, ValueEnum};
+use clap_complete_command::{CompletionBackend, Shell};
+
+/// A shell of our own, which only lists the subcommands
+#[derive(Clone, Copy)]
+struct Mine;
+
+impl clap_complete::Generator for Mine {
+    fn file_name(&self, name: &str) -> String {
+        format!("{name}.mine")
+    }
+
+    fn generate(&self, cmd: &Command, buf: &mut dyn std::io::Write) {
+        for subcommand in cmd.get_subcommands() {
+            writeln!(buf, "{} {}", cmd.get_name(), subcommand.get_name())
+                .expect("failed to write completion file");
+        }
+    }
+}
+
+/// The built-in shells and ours
+#[derive(Clone, Copy)]
+enum Backend {
+    Shell(Shell),
+    Mine(Mine),
+}
+
+impl ValueEnum for Backend {
+    fn value_variants<'a>() -> &'a [Self] {
+        static VARIANTS: OnceLock<Vec<Backend>> = OnceLock::new();
+        VARIANTS.get_or_init(|| {
+            Shell::all()
+                .iter()
+                .copied()
+                .map(Self::Shell)
+                .chain([Self::Mine(Mine)])
+                .collect()
+        })
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        match self {
+            Self::Shell(shell) => shell.to_possible_value(),
+            Self::Mine(_) => Some(PossibleValue::new("mine")),
+        }
+    }
+}
+
+impl clap_complete::Generator for Backend {
+    fn file_name(&self, name: &str) -> String {
+        match self {
+            Self::Shell(shell) => shell.file_name(name),
+            Self::Mine(mine) => mine.file_name(name),
+        }
+    }
+
+    fn generate(&self, cmd: &Command, buf: &mut dyn std::io::Write) {
+        match self {
+            Self::Shell(shell) => clap_complete::Generator::generate(shell, cmd, buf),
+            Self::Mine(mine) => mine.generate(cmd, buf),
+        }
+    }
+}
+
+impl CompletionBackend for Backend {
+    fn name(&self) -> &str {
+        match self {
+            Self::Shell(shell) => shell.name(),
+            Self::Mine(_) => "mine",
+        }
+    }
+}
+
+fn build_cli() -> Command {
+    Command::new(env!("CARGO_PKG_NAME"))
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("completions")
+                .about("Generate shell completions")
+                .arg(
+                    Arg::new("shell")
+                        .value_name("SHELL")
+                        .help("The shell to generate the completions for")
+                        .required(true)
+                        .value_parser(clap::builder::EnumValueParser::<Backend>::new()),
+                ),
+        )
+}
+
+fn main() {
+    let matches = build_cli().get_matches();
+
+    match matches.subcommand() {
+        // e.g. `$ cli completions bash` or `$ cli completions mine`
+        Some(("completions", sub_matches)) => {
+            if let Some(backend) = sub_matches.get_one::<Backend>("shell") {
+                clap_complete_command::generate_with_backend(
+                    *backend,
+                    &mut build_cli(),
+                    &mut std::io::stdout(),
+                );
+            }
+        }
+        _ => {
+            unreachable!("Exhausted list of subcommands and `subcommand_required` prevents `None`")
+        }
+    }
+}
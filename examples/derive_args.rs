@@ -0,0 +1,24 @@
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete_command::CompletionsArgs;
+
+#[derive(Parser)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Generate shell completions
+    Completions(CompletionsArgs),
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    match cli.command {
+        Commands::Completions(args) => {
+            args.generate(&mut Cli::command(), &mut std::io::stdout());
+        }
+    }
+}
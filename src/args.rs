@@ -0,0 +1,48 @@
+use crate::Shell;
+
+/// The arguments of a `completions <SHELL>` subcommand for the derive API.
+///
+/// It can be a variant of a [`clap::Subcommand`] enum directly, or be flattened into other
+/// arguments with `#[command(flatten)]`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use clap::{CommandFactory, Parser, Subcommand};
+/// use clap_complete_command::CompletionsArgs;
+///
+/// #[derive(Parser)]
+/// struct Cli {
+///     #[command(subcommand)]
+///     command: Commands,
+/// }
+///
+/// #[derive(Subcommand)]
+/// enum Commands {
+///     /// Generate shell completions
+///     Completions(CompletionsArgs),
+/// }
+///
+/// let cli = Cli::parse();
+///
+/// match cli.command {
+///     // e.g. `$ cli completions bash`
+///     Commands::Completions(args) => {
+///         args.generate(&mut Cli::command(), &mut std::io::stdout());
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, clap::Args)]
+#[non_exhaustive]
+pub struct CompletionsArgs {
+    /// The shell to generate the completions for
+    #[arg(value_enum)]
+    pub shell: Shell,
+}
+
+impl CompletionsArgs {
+    /// See [`Shell::generate()`].
+    pub fn generate(&self, command: &mut clap::Command, buffer: &mut dyn std::io::Write) {
+        self.shell.generate(command, buffer);
+    }
+}
@@ -0,0 +1,30 @@
+use crate::Shell;
+
+/// A format completions can be generated in, like the shells of [`Shell`], for plugging a
+/// generator of your own into the same `completions` subcommand.
+///
+/// The generation itself is [`clap_complete::Generator`]'s, this adds the name the backend is
+/// selected by. To offer custom backends alongside the built-in shells, wrap [`Shell`] in an enum
+/// of your own implementing [`clap::ValueEnum`] and this trait, see `examples/custom_backend.rs`.
+pub trait CompletionBackend: clap_complete::Generator {
+    /// The name the backend is selected by, e.g. `bash`
+    fn name(&self) -> &str;
+}
+
+impl CompletionBackend for Shell {
+    fn name(&self) -> &str {
+        self.canonical_name()
+    }
+}
+
+/// Like [`Shell::generate()`] but for any `backend`, so custom backends get the same handling of
+/// the `command`'s bin name.
+pub fn generate_with_backend(
+    backend: impl CompletionBackend,
+    command: &mut clap::Command,
+    buffer: &mut dyn std::io::Write,
+) {
+    let bin_name = crate::bin_name(command);
+    debug_assert!(!bin_name.is_empty(), "{}", crate::EMPTY_BIN_NAME);
+    clap_complete::generate(backend, command, bin_name, buffer);
+}
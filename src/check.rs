@@ -0,0 +1,62 @@
+//! Smoke tests of generated completions, see [`crate::Shell::generate_and_check()`].
+
+/// Why completions failed [`crate::Shell::generate_and_check()`]
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum CheckError {
+    /// The completions are empty or only whitespace
+    Empty,
+    /// The completions don't mention the bin name they complete, or it's empty
+    MissingBinName(String),
+    /// The completions don't mention this visible top-level subcommand
+    MissingSubcommand(String),
+    /// The completions aren't valid UTF-8
+    InvalidUtf8(std::string::FromUtf8Error),
+}
+
+impl std::fmt::Display for CheckError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => f.write_str("the completions are empty"),
+            Self::MissingBinName(bin_name) if bin_name.is_empty() => {
+                f.write_str("the command has an empty bin name")
+            }
+            Self::MissingBinName(bin_name) => {
+                write!(f, "the completions don't mention the bin name `{bin_name}`")
+            }
+            Self::MissingSubcommand(name) => {
+                write!(f, "the completions don't mention the subcommand `{name}`")
+            }
+            Self::InvalidUtf8(error) => write!(f, "the completions aren't valid UTF-8: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for CheckError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidUtf8(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Checks that `completions` for `command`, whose bin name is `bin_name`, aren't empty and mention
+/// the bin name and each visible top-level subcommand
+pub fn check(completions: &str, command: &clap::Command, bin_name: &str) -> Result<(), CheckError> {
+    if completions.trim().is_empty() {
+        return Err(CheckError::Empty);
+    }
+    if bin_name.is_empty() || !completions.contains(bin_name) {
+        return Err(CheckError::MissingBinName(bin_name.to_owned()));
+    }
+    command
+        .get_subcommands()
+        .filter(|subcommand| !subcommand.is_hide_set() && subcommand.get_name() != "help")
+        .find(|subcommand| !completions.contains(subcommand.get_name()))
+        .map_or(Ok(()), |subcommand| {
+            Err(CheckError::MissingSubcommand(
+                subcommand.get_name().to_owned(),
+            ))
+        })
+}
@@ -0,0 +1,145 @@
+//! A generator for clink, as no crate generates its completions.
+//!
+//! clink completes cmd.exe commands with Lua scripts which describe them through
+//! `clink.argmatcher`. Each argmatcher lists a command's flags and the matches of each argument
+//! position, and linking a match or flag to another argmatcher (with `..`) describes what follows
+//! it: a subcommand's own arguments, or an option's value.
+
+use clap::ValueHint;
+
+use crate::walk;
+
+pub fn generate(command: &clap::Command, buffer: &mut dyn std::io::Write) {
+    let bin_name = command.get_bin_name().unwrap_or_else(|| command.get_name());
+
+    let script = format!(
+        "clink.argmatcher({}){}\n",
+        lua_string(bin_name),
+        argmatcher(command, 0)
+    );
+
+    buffer
+        .write_all(script.as_bytes())
+        .expect("failed to write completion file");
+}
+
+/// The method calls describing `command` on an argmatcher, indented by `depth` levels
+fn argmatcher(command: &clap::Command, depth: usize) -> String {
+    let indent = "    ".repeat(depth + 1);
+    let mut flags = Vec::new();
+    let mut descriptions = Vec::new();
+
+    for arg in walk::options(command) {
+        let value = arg
+            .get_action()
+            .takes_values()
+            .then(|| format!(" .. clink.argmatcher():addarg({})", value_matches(arg)));
+        for name in walk::option_names(arg) {
+            if let Some(help) = arg.get_help() {
+                descriptions.push((name.clone(), help.to_string()));
+            }
+            flags.push(format!(
+                "{}{}",
+                lua_string(&name),
+                value.as_deref().unwrap_or_default()
+            ));
+        }
+    }
+
+    let mut first_matches = Vec::new();
+    for subcommand in walk::subcommands(command) {
+        let linked = format!(
+            " .. clink.argmatcher(){}",
+            argmatcher(subcommand, depth + 2)
+        );
+        for name in walk::subcommand_names(subcommand) {
+            if let Some(about) = subcommand.get_about() {
+                descriptions.push((name.to_owned(), about.to_string()));
+            }
+            first_matches.push(format!("{}{linked}", lua_string(name)));
+        }
+    }
+
+    let positionals = walk::positionals(command).collect::<Vec<_>>();
+    let mut positions = Vec::new();
+    if !first_matches.is_empty() {
+        positions.push(lua_table(&first_matches, depth + 1));
+    }
+    for arg in &positionals {
+        positions.push(value_matches(arg));
+    }
+
+    let mut calls = String::new();
+    for position in positions {
+        calls.push_str(&format!("\n{indent}:addarg({position})"));
+    }
+    if !flags.is_empty() {
+        calls.push_str(&format!(
+            "\n{indent}:addflags({})",
+            lua_table(&flags, depth + 1)
+        ));
+    }
+    if !descriptions.is_empty() {
+        let descriptions = descriptions
+            .iter()
+            .map(|(name, description)| {
+                format!("[{}] = {}", lua_string(name), lua_string(description))
+            })
+            .collect::<Vec<_>>();
+        calls.push_str(&format!(
+            "\n{indent}:adddescriptions({})",
+            lua_table(&descriptions, depth + 1)
+        ));
+    }
+    if !positionals.iter().any(|arg| walk::completes_files(arg)) {
+        calls.push_str(&format!("\n{indent}:nofiles()"));
+    }
+    calls
+}
+
+/// The Lua table or match generator completing the value of `arg`
+fn value_matches(arg: &clap::Arg) -> String {
+    let values = walk::possible_values(arg);
+    if !values.is_empty() {
+        let values = values
+            .iter()
+            .map(|value| lua_string(value))
+            .collect::<Vec<_>>();
+        format!("{{ {} }}", values.join(", "))
+    } else if arg.get_value_hint() == ValueHint::DirPath {
+        "clink.dirmatches".to_owned()
+    } else if walk::completes_files(arg) {
+        "clink.filematches".to_owned()
+    } else {
+        "{}".to_owned()
+    }
+}
+
+/// A Lua table of `entries`, each on its own line indented one level more than `depth`
+fn lua_table(entries: &[String], depth: usize) -> String {
+    let indent = "    ".repeat(depth);
+    let entries = entries
+        .iter()
+        .map(|entry| format!("{indent}    {entry},"))
+        .collect::<Vec<_>>();
+    format!("{{\n{}\n{indent}}}", entries.join("\n"))
+}
+
+fn lua_string(value: &str) -> String {
+    let mut string = String::from("\"");
+    for character in value.chars() {
+        match character {
+            '\\' => string.push_str("\\\\"),
+            '"' => string.push_str("\\\""),
+            '\n' => string.push_str("\\n"),
+            '\r' => string.push_str("\\r"),
+            '\t' => string.push_str("\\t"),
+            character if character.is_control() => {
+                string.push_str(&format!("\\{:03}", u32::from(character)));
+            }
+            _ => string.push(character),
+        }
+    }
+    string.push('"');
+    string
+}
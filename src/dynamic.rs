@@ -0,0 +1,134 @@
+//! A `completions` subcommand offering both the static [`Shell`] scripts and the registration for
+//! [`clap_complete`]'s dynamic completions.
+//!
+//! `clap_complete` no longer has a `CompleteCommand` subcommand for dynamic completions, they're
+//! driven by [`clap_complete::CompleteEnv`] instead: the registration script calls the binary with
+//! the `COMPLETE` environment variable set, and [`clap_complete::CompleteEnv::complete()`] answers
+//! before any arguments are parsed. [`completions_command()`] builds a `completions <SHELL>`
+//! subcommand which prints the static script as usual, or with `--dynamic` the registration script,
+//! so both are set up the same way.
+//!
+//! The static scripts don't need the binary to take part in completion, but can't complete values
+//! computed at runtime. The dynamic registration calls the binary for every completion, so it must
+//! be on `PATH` and `main` must start with [`clap_complete::CompleteEnv::complete()`] for the same
+//! command. Only bash, elvish, fish, PowerShell and zsh support dynamic completions.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use clap::Command;
+//! use clap_complete::CompleteEnv;
+//! use clap_complete_command::dynamic;
+//!
+//! fn build_cli() -> Command {
+//!     Command::new(env!("CARGO_PKG_NAME"))
+//!         .subcommand_required(true)
+//!         .subcommand(dynamic::completions_command())
+//! }
+//!
+//! CompleteEnv::with_factory(build_cli).complete();
+//!
+//! let matches = build_cli().get_matches();
+//!
+//! match matches.subcommand() {
+//!     // e.g. `$ cli completions bash` or `$ cli completions bash --dynamic`
+//!     Some(("completions", sub_matches)) => {
+//!         dynamic::generate(sub_matches, &mut build_cli(), &mut std::io::stdout())
+//!             .expect("failed to write completions");
+//!     }
+//!     _ => {
+//!         unreachable!("Exhausted list of subcommands and `subcommand_required` prevents `None`")
+//!     }
+//! }
+//! ```
+
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use clap_complete::env::Shells;
+
+use crate::Shell;
+
+/// The environment variable [`clap_complete::CompleteEnv`] reads by default
+const VAR: &str = "COMPLETE";
+
+/// The `completions <SHELL> [--output <FILE>] [--dynamic]` subcommand, see [`generate()`].
+#[must_use]
+pub fn completions_command() -> Command {
+    crate::completions_subcommand().arg(
+        Arg::new("dynamic")
+            .long("dynamic")
+            .help("Register completions which are computed by calling the binary")
+            .action(ArgAction::SetTrue),
+    )
+}
+
+/// Writes the completions selected by the `matches` of [`completions_command()`] for `command`,
+/// to its `--output` file if it has one instead of `buffer`.
+///
+/// Without `--dynamic` this is [`Shell::generate()`], otherwise see [`write_registration()`].
+///
+/// # Errors
+///
+/// See [`write_registration()`].
+///
+/// # Panics
+///
+/// If `matches` aren't from [`completions_command()`].
+pub fn generate(
+    matches: &ArgMatches,
+    command: &mut Command,
+    buffer: &mut dyn std::io::Write,
+) -> Result<(), std::io::Error> {
+    let shell = *matches
+        .get_one::<Shell>("shell")
+        .expect("`shell` is required");
+    if let Some(output) = matches.get_one::<std::path::PathBuf>("output") {
+        if let Some(dir) = output.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut file = std::fs::File::create(output)?;
+        return write(shell, matches.get_flag("dynamic"), command, &mut file);
+    }
+    write(shell, matches.get_flag("dynamic"), command, buffer)
+}
+
+fn write(
+    shell: Shell,
+    dynamic: bool,
+    command: &mut Command,
+    buffer: &mut dyn std::io::Write,
+) -> Result<(), std::io::Error> {
+    if dynamic {
+        write_registration(shell, command, buffer)
+    } else {
+        shell.generate(command, buffer);
+        Ok(())
+    }
+}
+
+/// Writes the script registering [`clap_complete::CompleteEnv`]'s dynamic completions of `command`
+/// for `shell`, the same as running the binary with `COMPLETE=<shell>`.
+///
+/// The script calls the `command`'s bin name, falling back to the `command`'s name.
+///
+/// # Errors
+///
+/// If `shell` doesn't support dynamic completions, or if writing to `buffer` fails.
+pub fn write_registration(
+    shell: Shell,
+    command: &Command,
+    buffer: &mut dyn std::io::Write,
+) -> Result<(), std::io::Error> {
+    let shells = Shells::builtins();
+    // The completers are named like the shells' possible values
+    let completer = Some(shell)
+        .filter(Shell::supports_dynamic)
+        .and_then(|shell| shells.completer(shell.canonical_name()))
+        .ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!("{shell:?} doesn't support dynamic completions"),
+            )
+        })?;
+    let bin_name = crate::checked_bin_name(command)?;
+    completer.write_registration(VAR, command.get_name(), &bin_name, &bin_name, buffer)
+}
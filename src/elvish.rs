@@ -0,0 +1,27 @@
+//! Adjustments on top of [`clap_complete::Shell::Elvish`]'s output.
+
+const COMPLETER_START: &str = "set edit:completion:arg-completer[";
+const COMPLETER_END: &str = "] = {|@words|\n";
+
+/// Turns the script into a module defining the completer as its `complete` function, which it
+/// registers for the command when it's imported with `use`
+pub fn module(script: &str) -> String {
+    let Some(start) = script.find(COMPLETER_START) else {
+        return script.to_owned();
+    };
+    let Some(end) = script[start..].find(COMPLETER_END).map(|end| start + end) else {
+        return script.to_owned();
+    };
+    let bin_name = &script[start + COMPLETER_START.len()..end];
+
+    let mut module = format!(
+        "{}fn complete {{|@words|\n{}",
+        &script[..start],
+        &script[end + COMPLETER_END.len()..]
+    );
+    if !module.ends_with('\n') {
+        module.push('\n');
+    }
+    module.push_str(&format!("\n{COMPLETER_START}{bin_name}] = $complete~\n"));
+    module
+}
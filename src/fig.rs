@@ -0,0 +1,37 @@
+//! Adjustments on top of [`clap_complete_fig::Fig`]'s output.
+
+/// Sets the `icon` of the spec and each of its subcommands
+pub fn with_icon(script: &str, icon: &str) -> String {
+    let icon = icon.replace('\\', "\\\\").replace('"', "\\\"");
+    // The indentation of the `name`s of the spec and of the subcommands in each open `subcommands`
+    // array
+    let mut name_indents = vec![2];
+
+    let mut spec = String::new();
+    for line in script.split_inclusive('\n') {
+        spec.push_str(line);
+
+        let indent = line.len() - line.trim_start().len();
+        let content = line.trim();
+        match content {
+            "subcommands: [" => name_indents.push(indent + 4),
+            _ if content.starts_with(']') && name_indents.last() == Some(&(indent + 4)) => {
+                name_indents.pop();
+            }
+            _ if content.starts_with("name: \"") && name_indents.last() == Some(&indent) => {
+                spec.push_str(&format!("{}icon: \"{icon}\",\n", " ".repeat(indent)));
+            }
+            _ => {}
+        }
+    }
+    spec
+}
+
+/// Sets the `about` of `command` and its subcommands to their `long_about`, if they have one
+pub fn long_about(command: clap::Command) -> clap::Command {
+    let command = match command.get_long_about().cloned() {
+        Some(long_about) => command.about(long_about),
+        None => command,
+    };
+    command.mut_subcommands(long_about)
+}
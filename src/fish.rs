@@ -0,0 +1,156 @@
+//! Adjustments on top of [`clap_complete::Shell::Fish`]'s output.
+
+use clap_complete::Generator;
+
+pub fn generate(command: &clap::Command, buffer: &mut dyn std::io::Write) {
+    let mut script = Vec::new();
+    clap_complete::Shell::Fish.generate(command, &mut script);
+    let script = String::from_utf8(script).expect("fish completions should be valid UTF-8");
+
+    let script = optional_values(command, &script);
+
+    buffer
+        .write_all(script.as_bytes())
+        .expect("failed to write completion file");
+}
+
+/// Removes the lines completing the `help` and `version` flags, they're still parsed as
+/// `clap_complete` lists them for `argparse` separately
+pub fn without_help_and_version(command: &clap::Command, script: &str) -> String {
+    let mut specs = Vec::new();
+    collect_help_and_version_specs(command, &mut specs);
+
+    script
+        .split_inclusive('\n')
+        .filter(|line| {
+            !specs.iter().any(|spec| {
+                find_spec(line, spec).is_some_and(|end| {
+                    let rest = line[end..].trim_end_matches('\n');
+                    rest.is_empty() || (rest.starts_with(" -d '") && rest.ends_with('\''))
+                })
+            })
+        })
+        .collect()
+}
+
+fn collect_help_and_version_specs(command: &clap::Command, specs: &mut Vec<String>) {
+    for arg in command.get_arguments() {
+        if matches!(
+            arg.get_action(),
+            clap::ArgAction::Help
+                | clap::ArgAction::HelpShort
+                | clap::ArgAction::HelpLong
+                | clap::ArgAction::Version
+        ) {
+            specs.push(option_spec(arg));
+        }
+    }
+
+    for subcommand in command.get_subcommands() {
+        collect_help_and_version_specs(subcommand, specs);
+    }
+}
+
+/// `clap_complete` marks every option taking a value with `-r` (require parameter), which makes fish
+/// insist on a value for options like `--color[=WHEN]` that use `default_missing_value`.
+///
+/// Options are matched by their `-s`/`-l` spec, so a spec is only relaxed when no option sharing it
+/// anywhere in the tree requires a value.
+fn optional_values(command: &clap::Command, script: &str) -> String {
+    let mut optional = Vec::new();
+    let mut required = Vec::new();
+    collect_option_specs(command, &mut optional, &mut required);
+    optional.retain(|spec| !required.contains(spec));
+
+    if optional.is_empty() {
+        return script.to_owned();
+    }
+
+    script
+        .split_inclusive('\n')
+        .map(|line| {
+            optional
+                .iter()
+                .find_map(|spec| strip_require_parameter(line, spec))
+                .unwrap_or_else(|| line.to_owned())
+        })
+        .collect()
+}
+
+fn collect_option_specs(
+    command: &clap::Command,
+    optional: &mut Vec<String>,
+    required: &mut Vec<String>,
+) {
+    for arg in command.get_opts() {
+        let Some(num_args) = arg.get_num_args() else {
+            continue;
+        };
+        let spec = option_spec(arg);
+        if num_args.min_values() == 0 {
+            optional.push(spec);
+        } else {
+            required.push(spec);
+        }
+    }
+
+    for subcommand in command.get_subcommands() {
+        collect_option_specs(subcommand, optional, required);
+    }
+}
+
+/// The `-s`/`-l` flags `clap_complete` emits for `arg`
+fn option_spec(arg: &clap::Arg) -> String {
+    let mut spec = String::new();
+    for short in arg.get_short_and_visible_aliases().unwrap_or_default() {
+        spec.push_str(&format!(" -s {short}"));
+    }
+    for long in arg.get_long_and_visible_aliases().unwrap_or_default() {
+        spec.push_str(&format!(
+            " -l {}",
+            long.replace('\\', "\\\\").replace('\'', "\\'")
+        ));
+    }
+    spec
+}
+
+/// The end of `spec` in `line`
+fn find_spec(line: &str, spec: &str) -> Option<usize> {
+    let start = line
+        .match_indices(spec)
+        .map(|(start, _)| start)
+        .find(|start| {
+            let end = start + spec.len();
+            // ` -l color` must not match the tail of another option's ` -s c -l color`, or the start
+            // of ` -l color-mode`
+            line[..*start].rsplit(' ').nth(1) != Some("-s")
+                && matches!(line[end..].chars().next(), None | Some(' ' | '\n'))
+        })?;
+    Some(start + spec.len())
+}
+
+/// Removes the ` -r` following `spec` (and its `-d '...'` description) in `line`, if any
+fn strip_require_parameter(line: &str, spec: &str) -> Option<String> {
+    let mut end = find_spec(line, spec)?;
+
+    if let Some(description) = line[end..].strip_prefix(" -d '") {
+        let mut escaped = false;
+        let length = description.char_indices().find_map(|(index, character)| {
+            match (escaped, character) {
+                (false, '\\') => escaped = true,
+                (false, '\'') => return Some(index),
+                _ => escaped = false,
+            }
+            None
+        })?;
+        end += " -d '".len() + length + '\''.len_utf8();
+    }
+
+    if !line[end..].starts_with(" -r") {
+        return None;
+    }
+
+    let mut line = line.to_owned();
+    line.replace_range(end..end + " -r".len(), "");
+    Some(line)
+}
@@ -0,0 +1,42 @@
+//! Comments identifying generated completions.
+
+use crate::Shell;
+
+/// Lines which have to stay first for the completions to be picked up
+const LEADING_LINES: &[&str] = &["#compdef ", "# yaml-language-server: "];
+
+/// Adds `header` as a comment at the start of `script`
+pub fn insert(shell: Shell, script: &str, header: &str) -> String {
+    let prefix = comment_prefix(shell);
+    let comment = header
+        .lines()
+        .map(|line| {
+            if line.is_empty() {
+                format!("{prefix}\n")
+            } else {
+                format!("{prefix} {line}\n")
+            }
+        })
+        .collect::<String>();
+
+    let leading = script
+        .split_inclusive('\n')
+        .next()
+        .filter(|line| LEADING_LINES.iter().any(|start| line.starts_with(start)))
+        .unwrap_or_default();
+
+    format!("{leading}{comment}{}", &script[leading.len()..])
+}
+
+const fn comment_prefix(shell: Shell) -> &'static str {
+    match shell {
+        #[cfg(feature = "clink")]
+        Shell::Clink => "--",
+        #[cfg(feature = "fig")]
+        Shell::Fig => "//",
+        #[cfg(feature = "man")]
+        Shell::Man => ".\\\"",
+        #[allow(unreachable_patterns)]
+        _ => "#",
+    }
+}
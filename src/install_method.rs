@@ -0,0 +1,14 @@
+//! How each shell's completions are installed.
+
+/// How users install a shell's completions, see [`crate::Shell::install_method()`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InstallMethod {
+    /// The shell's startup file evaluates the output of the `completions` subcommand every time
+    /// the shell starts, with the one-liner from [`crate::Shell::source_hint()`], e.g.
+    /// `eval "$(cli completions bash)"`
+    Eval,
+    /// The completions are saved to a file once, which the shell or tool loads from a directory or
+    /// its startup file, see [`crate::Shell::setup_doc()`]
+    File,
+}
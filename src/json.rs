@@ -0,0 +1,127 @@
+//! A machine-readable description of a command's completions, for editor integrations and other
+//! tools which aren't shells.
+
+use serde_json::{json, Value};
+
+/// Writes the options, positionals and subcommands of `command` as pretty-printed JSON.
+///
+/// Every command is an object with its `name`, `about`, visible `aliases`, `options`,
+/// `positionals` and `subcommands`. Options have their `id`, `longs` and `shorts` (with visible
+/// aliases, without dashes), `help`, whether they're `required`, whether they `takes_value`,
+/// their `value_names`, `value_hint` (e.g. `"file-path"`, or `null` if unknown) and
+/// `possible_values` (objects with a `name` and `help`). Positionals have the same fields except
+/// `longs`, `shorts` and `takes_value`. Hidden arguments, subcommands and possible values are left
+/// out.
+///
+/// Object keys are sorted, so the output only changes when the command does.
+///
+/// # Panics
+///
+/// If writing to `buffer` fails, like [`crate::Shell::generate()`].
+///
+/// # Examples
+///
+/// ```
+/// let mut command = clap::Command::new("app").arg(clap::Arg::new("verbose").long("verbose"));
+///
+/// let mut buffer = Vec::new();
+/// clap_complete_command::generate_json(&mut command, &mut buffer);
+///
+/// let json: serde_json::Value = serde_json::from_slice(&buffer).unwrap();
+/// assert_eq!(json["options"][0]["longs"][0], "verbose");
+/// ```
+pub fn generate_json(command: &mut clap::Command, buffer: &mut dyn std::io::Write) {
+    command.build();
+    let mut json =
+        serde_json::to_string_pretty(&command_json(command)).expect("JSON values always serialize");
+    json.push('\n');
+
+    buffer
+        .write_all(json.as_bytes())
+        .expect("failed to write completion file");
+}
+
+fn command_json(command: &clap::Command) -> Value {
+    let options = command
+        .get_arguments()
+        .filter(|arg| !arg.is_positional() && !arg.is_hide_set())
+        .map(|arg| {
+            let mut option = arg_json(arg);
+            option["longs"] = json!(arg.get_long_and_visible_aliases().unwrap_or_default());
+            option["shorts"] = json!(arg
+                .get_short_and_visible_aliases()
+                .unwrap_or_default()
+                .into_iter()
+                .map(String::from)
+                .collect::<Vec<_>>());
+            option["takes_value"] = json!(arg.get_action().takes_values());
+            option
+        })
+        .collect::<Vec<_>>();
+
+    let positionals = command
+        .get_positionals()
+        .filter(|arg| !arg.is_hide_set())
+        .map(arg_json)
+        .collect::<Vec<_>>();
+
+    let subcommands = command
+        .get_subcommands()
+        .filter(|subcommand| !subcommand.is_hide_set())
+        .map(command_json)
+        .collect::<Vec<_>>();
+
+    json!({
+        "name": command.get_name(),
+        "about": command.get_about().map(ToString::to_string),
+        "aliases": command.get_visible_aliases().collect::<Vec<_>>(),
+        "options": options,
+        "positionals": positionals,
+        "subcommands": subcommands,
+    })
+}
+
+/// The fields options and positionals have in common
+fn arg_json(arg: &clap::Arg) -> Value {
+    let possible_values = arg
+        .get_possible_values()
+        .into_iter()
+        .filter(|value| !value.is_hide_set())
+        .map(|value| {
+            json!({
+                "name": value.get_name(),
+                "help": value.get_help().map(ToString::to_string),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "id": arg.get_id().as_str(),
+        "help": arg.get_help().map(ToString::to_string),
+        "required": arg.is_required_set(),
+        "value_names": arg
+            .get_value_names()
+            .unwrap_or_default()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+        "value_hint": value_hint(arg.get_value_hint()),
+        "possible_values": possible_values,
+    })
+}
+
+/// `hint` in kebab case, e.g. `file-path` for [`clap::ValueHint::FilePath`]
+fn value_hint(hint: clap::ValueHint) -> Option<String> {
+    if hint == clap::ValueHint::Unknown {
+        return None;
+    }
+    // `ValueHint` is non-exhaustive, so its variant names are used rather than a match
+    let mut name = String::new();
+    for character in format!("{hint:?}").chars() {
+        if character.is_ascii_uppercase() && !name.is_empty() {
+            name.push('-');
+        }
+        name.push(character.to_ascii_lowercase());
+    }
+    Some(name)
+}
@@ -34,24 +34,12 @@
 //! ## Builder
 //!
 //! ```no_run
-//! use clap::{Arg, Command};
+//! use clap::Command;
 //!
 //! fn build_cli() -> Command {
 //!     Command::new(env!("CARGO_PKG_NAME"))
 //!         .subcommand_required(true)
-//!         .subcommand(
-//!             Command::new("completions")
-//!                 .about("Generate shell completions")
-//!                 .arg(
-//!                     Arg::new("shell")
-//!                         .value_name("SHELL")
-//!                         .help("The shell to generate the completions for")
-//!                         .required(true)
-//!                         .value_parser(
-//!                             clap::builder::EnumValueParser::<clap_complete_command::Shell>::new(),
-//!                         ),
-//!                 ),
-//!         )
+//!         .subcommand(clap_complete_command::completions_subcommand())
 //! }
 //!
 //! let matches = build_cli().get_matches();
@@ -71,18 +59,18 @@
 //! ```
 
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
-
 #![warn(clippy::cast_lossless)]
 #![warn(clippy::cast_possible_wrap)]
 #![warn(clippy::default_trait_access)]
 #![warn(clippy::else_if_without_else)]
-#![warn(clippy::empty_enum)]
+#![warn(clippy::empty_enums)]
 #![warn(clippy::empty_line_after_outer_attr)]
 #![warn(clippy::enum_glob_use)]
 #![warn(clippy::equatable_if_let)]
 #![warn(clippy::float_cmp)]
 #![warn(clippy::fn_params_excessive_bools)]
 #![warn(clippy::get_unwrap)]
+#![warn(clippy::implicit_clone)]
 #![warn(clippy::inefficient_to_string)]
 #![warn(clippy::integer_division)]
 #![warn(clippy::let_unit_value)]
@@ -94,7 +82,6 @@
 #![warn(clippy::many_single_char_names)]
 #![warn(clippy::map_unwrap_or)]
 #![warn(clippy::match_bool)]
-#![warn(clippy::match_on_vec_items)]
 #![warn(clippy::match_same_arms)]
 #![warn(clippy::match_wild_err_arm)]
 #![warn(clippy::match_wildcard_for_single_variants)]
@@ -109,7 +96,6 @@
 #![warn(clippy::redundant_pub_crate)]
 #![warn(clippy::single_match_else)]
 #![warn(clippy::str_to_string)]
-#![warn(clippy::string_to_string)]
 #![warn(clippy::trait_duplication_in_bounds)]
 #![warn(clippy::unused_async)]
 #![warn(clippy::unused_self)]
@@ -118,10 +104,96 @@
 #![warn(clippy::wildcard_imports)]
 #![warn(clippy::zero_sized_map_values)]
 
-use std::{ffi::OsString, path::PathBuf};
+#[cfg(not(any(
+    feature = "bash",
+    feature = "carapace",
+    feature = "clink",
+    feature = "elvish",
+    feature = "fig",
+    feature = "fish",
+    feature = "man",
+    feature = "murex",
+    feature = "nushell",
+    feature = "powershell",
+    feature = "tcsh",
+    feature = "xonsh",
+    feature = "zsh",
+)))]
+compile_error!("at least one shell's feature has to be enabled, e.g. `bash`");
+
+#[cfg(feature = "derive")]
+mod args;
+mod backend;
+mod check;
+#[cfg(feature = "clink")]
+mod clink;
+#[cfg(feature = "dynamic")]
+pub mod dynamic;
+#[cfg(feature = "elvish")]
+mod elvish;
+#[cfg(feature = "fig")]
+mod fig;
+#[cfg(feature = "fish")]
+mod fish;
+mod header;
+mod install_method;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "murex")]
+mod murex;
+#[cfg(feature = "nushell")]
+mod nu;
+mod options;
+mod overwrite;
+mod powershell;
+#[cfg(feature = "serde")]
+mod serialization;
+mod startup_cost;
+#[cfg(feature = "tcsh")]
+mod tcsh;
+mod unavailable;
+mod visible;
+#[cfg(any(
+    feature = "clink",
+    feature = "murex",
+    feature = "tcsh",
+    feature = "xonsh"
+))]
+mod walk;
+#[cfg(feature = "xonsh")]
+mod xonsh;
+#[cfg(feature = "zsh")]
+mod zsh;
+
+use std::{
+    collections::HashMap,
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
 
 use clap::ValueEnum;
 
+#[cfg(feature = "derive")]
+pub use args::CompletionsArgs;
+pub use backend::{generate_with_backend, CompletionBackend};
+pub use check::CheckError;
+pub use install_method::InstallMethod;
+#[cfg(feature = "json")]
+pub use json::generate_json;
+#[cfg(feature = "bash")]
+pub use options::BashForm;
+#[cfg(feature = "elvish")]
+pub use options::ElvishForm;
+pub use options::GenerateOptions;
+#[cfg(feature = "nushell")]
+pub use options::NuForm;
+#[cfg(feature = "zsh")]
+pub use options::ZshForm;
+pub use overwrite::OverwritePolicy;
+pub use powershell::PowerShellEdition;
+pub use startup_cost::StartupCost;
+pub use unavailable::UnavailableShellError;
+
 /// A [`clap::ValueEnum`] for available shell completions.
 ///
 /// # Examples
@@ -149,24 +221,12 @@ use clap::ValueEnum;
 /// ## Builder
 ///
 /// ```no_run
-/// use clap::{Arg, Command};
+/// use clap::Command;
 ///
 /// fn build_cli() -> Command {
 ///     Command::new(env!("CARGO_PKG_NAME"))
 ///         .subcommand_required(true)
-///         .subcommand(
-///             Command::new("completions")
-///                 .about("Generate shell completions")
-///                 .arg(
-///                     Arg::new("shell")
-///                         .value_name("SHELL")
-///                         .help("The shell to generate the completions for")
-///                         .required(true)
-///                         .value_parser(
-///                             clap::builder::EnumValueParser::<clap_complete_command::Shell>::new(),
-///                         ),
-///                 ),
-///         )
+///         .subcommand(clap_complete_command::completions_subcommand())
 /// }
 ///
 /// let matches = build_cli().get_matches();
@@ -182,187 +242,5283 @@ use clap::ValueEnum;
 ///     }
 /// }
 /// ```
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[non_exhaustive]
 pub enum Shell {
     /// Bourne Again SHell (bash)
+    ///
+    /// The same script can be sourced inline (e.g. `source <(cli completions bash)`) or installed
+    /// with [`Shell::generate_to()`]. bash-completion finds installed files by their name but still
+    /// expects them to register themselves, so both forms include the `complete -F` registration.
+    #[cfg(feature = "bash")]
     Bash,
     /// Carapace spec
     #[cfg(feature = "carapace")]
     Carapace,
+    /// clink, the cmd.exe enhancement
+    #[cfg(feature = "clink")]
+    Clink,
     /// Elvish shell
+    #[cfg(feature = "elvish")]
     Elvish,
     /// Fig
     #[cfg(feature = "fig")]
     Fig,
     /// Friendly Interactive SHell (fish)
+    #[cfg(feature = "fish")]
     Fish,
+    /// A man page, generated with [`clap_mangen`] rather than completing anything
+    #[cfg(feature = "man")]
+    Man,
+    /// murex, a shell with structured pipelines
+    #[cfg(feature = "murex")]
+    Murex,
     /// NUshell (nu)
     #[cfg(feature = "nushell")]
     Nu,
     /// PowerShell
+    #[cfg(feature = "powershell")]
     PowerShell,
+    /// TENEX C shell (tcsh)
+    #[cfg(feature = "tcsh")]
+    Tcsh,
+    /// xonsh, the Python-powered shell
+    #[cfg(feature = "xonsh")]
+    Xonsh,
     /// Z SHell (zsh)
+    #[cfg(feature = "zsh")]
     Zsh,
 }
 
 impl clap_complete::Generator for Shell {
     fn file_name(&self, name: &str) -> String {
         match self {
+            #[cfg(feature = "bash")]
             Self::Bash => clap_complete::Shell::Bash.file_name(name),
+            #[cfg(feature = "elvish")]
             Self::Elvish => clap_complete::Shell::Elvish.file_name(name),
+            #[cfg(feature = "fish")]
             Self::Fish => clap_complete::Shell::Fish.file_name(name),
+            #[cfg(feature = "powershell")]
             Self::PowerShell => clap_complete::Shell::PowerShell.file_name(name),
+            #[cfg(feature = "zsh")]
             Self::Zsh => clap_complete::Shell::Zsh.file_name(name),
 
             #[cfg(feature = "carapace")]
             Self::Carapace => carapace_spec_clap::Spec.file_name(name),
+            #[cfg(feature = "clink")]
+            Self::Clink => format!("{name}.lua"),
             #[cfg(feature = "fig")]
             Self::Fig => clap_complete_fig::Fig.file_name(name),
+            #[cfg(feature = "man")]
+            Self::Man => format!("{name}.1"),
+            #[cfg(feature = "murex")]
+            Self::Murex => format!("{name}.mx"),
             #[cfg(feature = "nushell")]
             Self::Nu => clap_complete_nushell::Nushell.file_name(name),
+            #[cfg(feature = "tcsh")]
+            Self::Tcsh => format!("{name}.tcsh"),
+            #[cfg(feature = "xonsh")]
+            Self::Xonsh => format!("{name}.xsh"),
         }
     }
 
     fn generate(&self, cmd: &clap::Command, buf: &mut dyn std::io::Write) {
+        // Hidden items are left out unless `GenerateOptions::include_hidden()` un-hid them. The man
+        // page leaves them out itself
+        let visible = visible::without_hidden(cmd);
         match self {
-            Self::Bash => clap_complete::Shell::Bash.generate(cmd, buf),
-            Self::Elvish => clap_complete::Shell::Elvish.generate(cmd, buf),
-            Self::Fish => clap_complete::Shell::Fish.generate(cmd, buf),
-            Self::PowerShell => clap_complete::Shell::PowerShell.generate(cmd, buf),
-            Self::Zsh => clap_complete::Shell::Zsh.generate(cmd, buf),
+            #[cfg(feature = "bash")]
+            Self::Bash => clap_complete::Shell::Bash.generate(&visible, buf),
+            #[cfg(feature = "elvish")]
+            Self::Elvish => clap_complete::Shell::Elvish.generate(&visible, buf),
+            #[cfg(feature = "fish")]
+            Self::Fish => fish::generate(&visible, buf),
+            #[cfg(feature = "powershell")]
+            Self::PowerShell => clap_complete::Shell::PowerShell.generate(&visible, buf),
+            #[cfg(feature = "zsh")]
+            Self::Zsh => zsh::generate(&visible, buf),
 
             #[cfg(feature = "carapace")]
-            Self::Carapace => carapace_spec_clap::Spec.generate(cmd, buf),
+            Self::Carapace => carapace_spec_clap::Spec.generate(&visible, buf),
+            #[cfg(feature = "clink")]
+            Self::Clink => clink::generate(&visible, buf),
             #[cfg(feature = "fig")]
-            Self::Fig => clap_complete_fig::Fig.generate(cmd, buf),
+            Self::Fig => clap_complete_fig::Fig.generate(&visible, buf),
+            #[cfg(feature = "man")]
+            Self::Man => clap_mangen::Man::new(cmd.clone())
+                .render(buf)
+                .expect("failed to write man page"),
+            #[cfg(feature = "murex")]
+            Self::Murex => murex::generate(&visible, buf),
             #[cfg(feature = "nushell")]
-            Self::Nu => clap_complete_nushell::Nushell.generate(cmd, buf),
+            Self::Nu => nu::generate(&visible, buf),
+            #[cfg(feature = "tcsh")]
+            Self::Tcsh => tcsh::generate(&visible, buf),
+            #[cfg(feature = "xonsh")]
+            Self::Xonsh => xonsh::generate(&visible, buf),
         }
     }
 }
 
 impl Shell {
-    /// See [`clap_complete::generate()`].
+    /// The shells available in this build, the same as [`ValueEnum::value_variants()`] without
+    /// needing to import the trait.
+    #[must_use]
+    pub fn variants_for_build() -> &'static [Self] {
+        Self::value_variants()
+    }
+
+    /// The shells available in this build, the same as [`Self::variants_for_build()`].
+    #[must_use]
+    pub fn all() -> &'static [Self] {
+        Self::value_variants()
+    }
+
+    /// The names of [`Self::all()`] as possible values, e.g. for listing the supported shells in
+    /// `--help`.
+    #[must_use]
+    pub fn names() -> Vec<&'static str> {
+        Self::all()
+            .iter()
+            .map(|shell| shell.canonical_name())
+            .collect()
+    }
+
+    /// The shell's possible value help, shown in `--help` and the completions of the `shell` arg
+    const fn description(self) -> &'static str {
+        match self {
+            #[cfg(feature = "bash")]
+            Self::Bash => "Bourne Again SHell",
+            #[cfg(feature = "elvish")]
+            Self::Elvish => "Elvish shell",
+            #[cfg(feature = "fish")]
+            Self::Fish => "Friendly Interactive SHell",
+            #[cfg(feature = "powershell")]
+            Self::PowerShell => "PowerShell",
+            #[cfg(feature = "zsh")]
+            Self::Zsh => "Z SHell",
+
+            #[cfg(feature = "carapace")]
+            Self::Carapace => "Carapace spec",
+            #[cfg(feature = "clink")]
+            Self::Clink => "clink for cmd.exe",
+            #[cfg(feature = "fig")]
+            Self::Fig => "Fig spec",
+            #[cfg(feature = "man")]
+            Self::Man => "Man page",
+            #[cfg(feature = "murex")]
+            Self::Murex => "murex shell",
+            #[cfg(feature = "nushell")]
+            Self::Nu => "NUshell",
+            #[cfg(feature = "tcsh")]
+            Self::Tcsh => "TENEX C shell",
+            #[cfg(feature = "xonsh")]
+            Self::Xonsh => "Python-powered shell",
+        }
+    }
+
+    /// Shorthands the shell's possible value also accepts, e.g. `ps` for PowerShell, which aren't
+    /// shown in `--help` and don't change [`Self::canonical_name()`]
+    const fn aliases(self) -> &'static [&'static str] {
+        match self {
+            #[cfg(feature = "powershell")]
+            Self::PowerShell => &["ps", "pwsh"],
+            #[cfg(feature = "nushell")]
+            Self::Nu => &["nu"],
+            #[allow(unreachable_patterns)]
+            _ => &[],
+        }
+    }
+
+    /// A value parser for the `shell` arg, like [`clap::builder::EnumValueParser`], which leaves the
+    /// `hidden` shells out of `--help` and the completions while still accepting them, e.g. to
+    /// support fig without advertising it.
     ///
-    /// The `command`'s bin name is used as the completion's bin name.
-    /// If the `command`'s bin name is not set, it will be set to the `command`'s name.
-    pub fn generate(self, command: &mut clap::Command, buffer: &mut dyn std::io::Write) {
-        let bin_name = command
-            .get_bin_name()
-            .unwrap_or_else(|| command.get_name())
-            .to_owned();
-        clap_complete::generate(self, command, bin_name, buffer)
+    /// # Examples
+    ///
+    /// ```
+    /// use clap_complete_command::Shell;
+    ///
+    /// # #[cfg(feature = "elvish")] {
+    /// let command = clap_complete_command::completions_subcommand()
+    ///     .mut_arg("shell", |arg| arg.value_parser(Shell::value_parser_hiding(&[Shell::Elvish])));
+    ///
+    /// let matches = command.get_matches_from(["completions", "elvish"]);
+    /// assert_eq!(matches.get_one::<Shell>("shell"), Some(&Shell::Elvish));
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn value_parser_hiding(
+        hidden: &[Self],
+    ) -> impl clap::builder::TypedValueParser<Value = Self> {
+        let values = Self::all()
+            .iter()
+            .map(|shell| {
+                shell
+                    .to_possible_value()
+                    .expect("no shells are skipped")
+                    .hide(hidden.contains(shell))
+            })
+            .collect::<Vec<_>>();
+        clap::builder::TypedValueParser::map(
+            clap::builder::PossibleValuesParser::new(values),
+            |name| {
+                // The name may be an alias, or in another case if the arg ignores case
+                Self::from_str(&name, true).expect("only the names of shells are possible values")
+            },
+        )
     }
 
-    /// See [`clap_complete::generate_to()`].
+    /// A value parser for the shell argument which ignores case, e.g. accepting `PowerShell` and
+    /// `Bash` as typed by users used to their capitalised names.
     ///
-    /// The `command`'s bin name is used as the completion's bin name.
-    /// If the `command`'s bin name is not set, it will be set to the `command`'s name.
-    pub fn generate_to<S>(
-        self,
-        command: &mut clap::Command,
-        out_dir: S,
-    ) -> Result<PathBuf, std::io::Error>
-    where
-        S: Into<OsString>,
-    {
-        let bin_name = command
-            .get_bin_name()
-            .unwrap_or_else(|| command.get_name())
-            .to_owned();
-        clap_complete::generate_to(self, command, bin_name, out_dir)
+    /// This is opt-in as [`clap::ValueEnum`] matches case-sensitively like other arguments, and
+    /// the lowercase names are still the ones in `--help` and the completions. Errors mention the
+    /// value as it was typed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clap_complete_command::Shell;
+    ///
+    /// let command = clap_complete_command::completions_subcommand()
+    ///     .mut_arg("shell", |arg| arg.value_parser(Shell::value_parser()));
+    ///
+    /// # #[cfg(feature = "powershell")] {
+    /// let matches = command.get_matches_from(["completions", "PowerShell"]);
+    /// assert_eq!(matches.get_one::<Shell>("shell"), Some(&Shell::PowerShell));
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn value_parser() -> impl clap::builder::TypedValueParser<Value = Self> {
+        IgnoreCaseValueParser
     }
-}
 
-// Hand-rolled to avoid depending on Clap's `derive` feature
-impl ValueEnum for Shell {
-    fn value_variants<'a>() -> &'a [Self] {
-        &[
-            Self::Bash,
+    /// Whether the shell called `name` is in [`Self::all()`], i.e. its feature is enabled in this
+    /// build, e.g. to explain that `fig` needs the `fig` feature rather than that it's unknown.
+    #[must_use]
+    pub fn is_available(name: &str) -> bool {
+        Self::all()
+            .iter()
+            .any(|shell| shell.canonical_name() == name)
+    }
+
+    /// The shell's name as a possible value, e.g. `"bash"`, in a const context.
+    ///
+    /// ```
+    /// use clap_complete_command::Shell;
+    ///
+    /// # #[cfg(feature = "bash")] {
+    /// const BASH: &str = Shell::Bash.canonical_name();
+    /// assert_eq!(BASH, "bash");
+    /// # }
+    /// ```
+    #[must_use]
+    pub const fn canonical_name(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "bash")]
+            Self::Bash => "bash",
             #[cfg(feature = "carapace")]
-            Self::Carapace,
-            Self::Elvish,
+            Self::Carapace => "carapace",
+            #[cfg(feature = "clink")]
+            Self::Clink => "clink",
+            #[cfg(feature = "elvish")]
+            Self::Elvish => "elvish",
             #[cfg(feature = "fig")]
-            Self::Fig,
-            Self::Fish,
+            Self::Fig => "fig",
+            #[cfg(feature = "fish")]
+            Self::Fish => "fish",
+            #[cfg(feature = "man")]
+            Self::Man => "man",
+            #[cfg(feature = "murex")]
+            Self::Murex => "murex",
             #[cfg(feature = "nushell")]
-            Self::Nu,
-            Self::PowerShell,
-            Self::Zsh,
-        ]
+            Self::Nu => "nushell",
+            #[cfg(feature = "powershell")]
+            Self::PowerShell => "powershell",
+            #[cfg(feature = "tcsh")]
+            Self::Tcsh => "tcsh",
+            #[cfg(feature = "xonsh")]
+            Self::Xonsh => "xonsh",
+            #[cfg(feature = "zsh")]
+            Self::Zsh => "zsh",
+        }
     }
 
-    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
-        Some(match self {
-            Self::Bash => clap::builder::PossibleValue::new("bash"),
+    /// The Cargo feature which enables the shell, e.g. `Some("fig")` for [`Shell::Fig`].
+    ///
+    /// Every shell has its own feature, so this is never `None`. The features of bash, elvish,
+    /// fish, nushell, PowerShell and zsh are enabled by default.
+    ///
+    /// With [`Self::all()`] this lists which shells each feature adds, e.g. for documentation.
+    #[must_use]
+    pub const fn feature_name(&self) -> Option<&'static str> {
+        match self {
+            #[cfg(feature = "bash")]
+            Self::Bash => Some("bash"),
+            #[cfg(feature = "elvish")]
+            Self::Elvish => Some("elvish"),
+            #[cfg(feature = "fish")]
+            Self::Fish => Some("fish"),
+            #[cfg(feature = "powershell")]
+            Self::PowerShell => Some("powershell"),
+            #[cfg(feature = "zsh")]
+            Self::Zsh => Some("zsh"),
+
             #[cfg(feature = "carapace")]
-            Self::Carapace => clap::builder::PossibleValue::new("carapace"),
-            Self::Elvish => clap::builder::PossibleValue::new("elvish"),
+            Self::Carapace => Some("carapace"),
+            #[cfg(feature = "clink")]
+            Self::Clink => Some("clink"),
             #[cfg(feature = "fig")]
-            Self::Fig => clap::builder::PossibleValue::new("fig"),
-            Self::Fish => clap::builder::PossibleValue::new("fish"),
+            Self::Fig => Some("fig"),
+            #[cfg(feature = "man")]
+            Self::Man => Some("man"),
+            #[cfg(feature = "murex")]
+            Self::Murex => Some("murex"),
             #[cfg(feature = "nushell")]
-            Self::Nu => clap::builder::PossibleValue::new("nushell"),
-            Self::PowerShell => clap::builder::PossibleValue::new("powershell"),
-            Self::Zsh => clap::builder::PossibleValue::new("zsh"),
-        })
+            Self::Nu => Some("nushell"),
+            #[cfg(feature = "tcsh")]
+            Self::Tcsh => Some("tcsh"),
+            #[cfg(feature = "xonsh")]
+            Self::Xonsh => Some("xonsh"),
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use clap::ValueEnum;
+    /// The `shell` of the [`completions_subcommand()`], from the `matches` of the command it's a
+    /// subcommand of or of the subcommand itself, or `None` if the subcommand wasn't used.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use clap_complete_command::Shell;
+    ///
+    /// fn build_cli() -> clap::Command {
+    ///     clap::Command::new("cli").subcommand(clap_complete_command::completions_subcommand())
+    /// }
+    ///
+    /// if let Some(shell) = Shell::from_matches(&build_cli().get_matches()) {
+    ///     shell.generate(&mut build_cli(), &mut std::io::stdout());
+    /// }
+    /// ```
+    #[must_use]
+    pub fn from_matches(matches: &clap::ArgMatches) -> Option<Self> {
+        let matches = match matches.subcommand() {
+            Some(("completions", sub_matches)) => sub_matches,
+            _ => matches,
+        };
+        matches.try_get_one::<Self>("shell").ok().flatten().copied()
+    }
 
-    macro_rules! check_shell_value_test {
-        ($test_name:ident, $shell:expr, $value:expr) => {
-            #[test]
-            fn $test_name() {
-                assert_eq!($shell.to_possible_value().unwrap().get_name(), $value);
+    /// The shell the user is running, from the basename of `$SHELL`, e.g. [`Shell::Fish`] for
+    /// `/usr/bin/fish`.
+    ///
+    /// On Windows, where `$SHELL` is usually unset, `%ComSpec%` is checked next, then PowerShell is
+    /// assumed if `%PSModulePath%` is set.
+    /// Returns `None` if the shell is unknown or isn't available in this build.
+    #[must_use]
+    pub fn from_env() -> Option<Self> {
+        if let Some(shell) = std::env::var_os("SHELL") {
+            return Self::from_shell_path(Path::new(&shell));
+        }
+
+        #[cfg(windows)]
+        {
+            if let Some(shell) = std::env::var_os("ComSpec")
+                .and_then(|shell| Self::from_shell_path(Path::new(&shell)))
+            {
+                return Some(shell);
+            }
+            #[cfg(feature = "powershell")]
+            if std::env::var_os("PSModulePath").is_some() {
+                return Some(Self::PowerShell);
             }
+        }
+
+        None
+    }
+
+    /// The shell running this process, from its parent process' executable, e.g. [`Shell::Fish`]
+    /// when fish was started from bash, where `$SHELL` would still be bash.
+    ///
+    /// The parent is only inspected on Linux, through `/proc`, so elsewhere this always returns
+    /// `None`. It also returns `None` if the parent isn't a known shell, e.g. when this process
+    /// was started by `cargo run` or another program, so [`Self::from_env()`] makes a good
+    /// fallback.
+    #[must_use]
+    pub fn from_parent_process() -> Option<Self> {
+        #[cfg(target_os = "linux")]
+        {
+            Self::from_process(std::os::unix::process::parent_id())
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            None
+        }
+    }
+
+    /// The shell process `pid` is running, from its executable, falling back to its name for
+    /// shells which are scripts run by an interpreter like xonsh
+    #[cfg(target_os = "linux")]
+    fn from_process(pid: u32) -> Option<Self> {
+        std::fs::read_link(format!("/proc/{pid}/exe"))
+            .ok()
+            .and_then(|exe| Self::from_shell_path(&exe))
+            .or_else(|| {
+                let name = std::fs::read_to_string(format!("/proc/{pid}/comm")).ok()?;
+                Self::from_shell_path(Path::new(name.trim_end()))
+            })
+    }
+
+    /// The shell whose executable is at `path`
+    fn from_shell_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        let name = name
+            .len()
+            .checked_sub(".exe".len())
+            .filter(|&end| {
+                name.get(end..)
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case(".exe"))
+            })
+            .map_or(name, |end| &name[..end]);
+
+        match name {
+            #[cfg(feature = "bash")]
+            "bash" => Some(Self::Bash),
+            #[cfg(feature = "elvish")]
+            "elvish" => Some(Self::Elvish),
+            #[cfg(feature = "fish")]
+            "fish" => Some(Self::Fish),
+            #[cfg(feature = "murex")]
+            "murex" => Some(Self::Murex),
+            #[cfg(feature = "nushell")]
+            "nu" => Some(Self::Nu),
+            #[cfg(feature = "powershell")]
+            "powershell" | "pwsh" => Some(Self::PowerShell),
+            #[cfg(feature = "tcsh")]
+            "tcsh" => Some(Self::Tcsh),
+            #[cfg(feature = "xonsh")]
+            "xonsh" => Some(Self::Xonsh),
+            #[cfg(feature = "zsh")]
+            "zsh" => Some(Self::Zsh),
+            _ => None,
+        }
+    }
+
+    /// The name of the file [`Self::generate_to()`] writes the completions of `bin_name` to, e.g.
+    /// `_cli` for zsh, without needing to import [`clap_complete::Generator`].
+    #[must_use]
+    pub fn file_name(&self, bin_name: &str) -> String {
+        clap_complete::Generator::file_name(self, bin_name)
+    }
+
+    /// The extension of [`Self::file_name()`] without the dot, e.g. `fish` for fish, or [`None`]
+    /// for zsh, whose completion files are only named with a `_` prefix.
+    #[must_use]
+    pub const fn extension(&self) -> Option<&'static str> {
+        match self {
+            #[cfg(feature = "bash")]
+            Self::Bash => Some("bash"),
+            #[cfg(feature = "elvish")]
+            Self::Elvish => Some("elv"),
+            #[cfg(feature = "fish")]
+            Self::Fish => Some("fish"),
+            #[cfg(feature = "powershell")]
+            Self::PowerShell => Some("ps1"),
+            #[cfg(feature = "zsh")]
+            Self::Zsh => None,
+
+            #[cfg(feature = "carapace")]
+            Self::Carapace => Some("yaml"),
+            #[cfg(feature = "clink")]
+            Self::Clink => Some("lua"),
+            #[cfg(feature = "fig")]
+            Self::Fig => Some("ts"),
+            #[cfg(feature = "man")]
+            Self::Man => Some("1"),
+            #[cfg(feature = "murex")]
+            Self::Murex => Some("mx"),
+            #[cfg(feature = "nushell")]
+            Self::Nu => Some("nu"),
+            #[cfg(feature = "tcsh")]
+            Self::Tcsh => Some("tcsh"),
+            #[cfg(feature = "xonsh")]
+            Self::Xonsh => Some("xsh"),
+        }
+    }
+
+    /// A markdown snippet for a CLI's documentation on setting up its completions for this shell.
+    ///
+    /// It assumes the completions are generated by a `completions` subcommand of `bin_name`, as in
+    /// this crate's examples, and uses the per-user locations on Linux.
+    #[must_use]
+    pub fn setup_doc(self, bin_name: &str) -> String {
+        let (language, steps) = self.setup_steps(bin_name);
+        format!("```{language}\n{steps}\n```\n")
+    }
+
+    /// Short instructions for users on installing the completions for this shell, e.g. for a
+    /// `completions --instructions` flag.
+    ///
+    /// Like [`Self::setup_doc()`], which has the same steps as markdown, it assumes the completions
+    /// are generated by a `completions` subcommand of `bin_name`.
+    #[must_use]
+    pub fn install_instructions(&self, bin_name: &str) -> String {
+        let intro = match self {
+            #[cfg(feature = "bash")]
+            Self::Bash => "Save the completions to bash-completion's per-user directory:",
+            #[cfg(feature = "elvish")]
+            Self::Elvish => "Load the completions from ~/.config/elvish/rc.elv:",
+            #[cfg(feature = "fish")]
+            Self::Fish => "Save the completions to ~/.config/fish/completions, which fish loads them from:",
+            #[cfg(feature = "powershell")]
+            Self::PowerShell => "Add this to your PowerShell profile, $PROFILE:",
+            #[cfg(feature = "zsh")]
+            Self::Zsh => "Save the completions to a directory on $fpath, e.g. ~/.zfunc, which compinit loads them from:",
+
+            #[cfg(feature = "carapace")]
+            Self::Carapace => "Save the spec to ~/.config/carapace/specs, which carapace loads it from:",
+            #[cfg(feature = "clink")]
+            Self::Clink => "Save the completions to clink's profile directory, which clink loads scripts from:",
+            #[cfg(feature = "fig")]
+            Self::Fig => "Save the spec and build it with Fig's @withfig/autocomplete-tools:",
+            #[cfg(feature = "man")]
+            Self::Man => "Save the man page to ~/.local/share/man/man1, where man finds it:",
+            #[cfg(feature = "murex")]
+            Self::Murex => "Save the completions and source them from ~/.murex_profile:",
+            #[cfg(feature = "nushell")]
+            Self::Nu => "Save the completions and source them from config.nu:",
+            #[cfg(feature = "tcsh")]
+            Self::Tcsh => "Save the completions and source them from ~/.tcshrc:",
+            #[cfg(feature = "xonsh")]
+            Self::Xonsh => "Save the completions to ~/.config/xonsh/rc.d, which xonsh runs on startup:",
         };
+
+        let (_, steps) = self.setup_steps(bin_name);
+        let instructions = format!("{intro}\n\n{}\n", indent(&steps));
+        // Shells which can also evaluate the completions show how, see `Self::install_method()`
+        match (self.startup_file(), self.source_hint(bin_name)) {
+            (Some(startup_file), Some(hint)) if !steps.contains(&hint) => format!(
+                "{instructions}\nOr load them from {startup_file}:\n\n{}\n",
+                indent(&hint)
+            ),
+            _ => instructions,
+        }
     }
 
-    check_shell_value_test!(test_shell_value_bash, Shell::Bash, "bash");
-    #[cfg(feature = "carapace")]
-    check_shell_value_test!(test_shell_value_carapace, Shell::Carapace, "carapace");
-    check_shell_value_test!(test_shell_value_elvish, Shell::Elvish, "elvish");
-    #[cfg(feature = "fig")]
-    check_shell_value_test!(test_shell_value_fig, Shell::Fig, "fig");
-    check_shell_value_test!(test_shell_value_fish, Shell::Fish, "fish");
-    #[cfg(feature = "nushell")]
-    check_shell_value_test!(test_shell_value_nushell, Shell::Nu, "nushell");
-    check_shell_value_test!(test_shell_value_powershell, Shell::PowerShell, "powershell");
-    check_shell_value_test!(test_shell_value_zsh, Shell::Zsh, "zsh");
+    /// The startup file of the [`InstallMethod::Eval`] shells, which [`Self::source_hint()`] is
+    /// for
+    const fn startup_file(self) -> Option<&'static str> {
+        match self {
+            #[cfg(feature = "bash")]
+            Self::Bash => Some("~/.bashrc"),
+            #[cfg(feature = "elvish")]
+            Self::Elvish => Some("~/.config/elvish/rc.elv"),
+            #[cfg(feature = "fish")]
+            Self::Fish => None,
+            #[cfg(feature = "powershell")]
+            Self::PowerShell => Some("$PROFILE"),
+            #[cfg(feature = "zsh")]
+            Self::Zsh => Some("~/.zshrc"),
 
-    #[test]
-    fn check_order() {
-        let names = Shell::value_variants()
-            .iter()
-            .map(|shell| shell.to_possible_value().unwrap().get_name().to_owned())
-            .collect::<Vec<_>>();
+            #[cfg(feature = "carapace")]
+            Self::Carapace => None,
+            #[cfg(feature = "clink")]
+            Self::Clink => None,
+            #[cfg(feature = "fig")]
+            Self::Fig => None,
+            #[cfg(feature = "man")]
+            Self::Man => None,
+            #[cfg(feature = "murex")]
+            Self::Murex => None,
+            #[cfg(feature = "nushell")]
+            Self::Nu => None,
+            #[cfg(feature = "tcsh")]
+            Self::Tcsh => Some("~/.tcshrc"),
+            #[cfg(feature = "xonsh")]
+            Self::Xonsh => Some("~/.xonshrc"),
+        }
+    }
 
-        let mut sorted = names.clone();
-        sorted.sort_unstable();
+    /// The language and commands of [`Self::setup_doc()`]
+    #[cfg_attr(
+        not(any(
+            feature = "carapace",
+            feature = "clink",
+            feature = "fig",
+            feature = "fish",
+            feature = "man",
+            feature = "murex",
+            feature = "nushell",
+            feature = "tcsh",
+            feature = "xonsh",
+            feature = "zsh",
+        )),
+        allow(unused_variables)
+    )]
+    fn setup_steps(self, bin_name: &str) -> (&'static str, String) {
+        let generate = format!(
+            "{bin_name} completions {}",
+            self.to_possible_value()
+                .expect("no shells are skipped")
+                .get_name(),
+        );
+        let file_name = self.file_name(bin_name);
 
-        assert_eq!(names, sorted);
+        let (language, steps) = match self {
+            #[cfg(feature = "bash")]
+            Self::Bash => (
+                "bash",
+                format!(
+                    "mkdir -p ~/.local/share/bash-completion/completions
+{generate} > ~/.local/share/bash-completion/completions/{bin_name}"
+                ),
+            ),
+            #[cfg(feature = "elvish")]
+            Self::Elvish => (
+                "elvish",
+                format!(
+                    "# in ~/.config/elvish/rc.elv
+eval ({generate} | slurp)"
+                ),
+            ),
+            #[cfg(feature = "fish")]
+            Self::Fish => (
+                "fish",
+                format!(
+                    "mkdir -p ~/.config/fish/completions
+{generate} > ~/.config/fish/completions/{file_name}"
+                ),
+            ),
+            #[cfg(feature = "powershell")]
+            Self::PowerShell => (
+                "powershell",
+                format!(
+                    "# in $PROFILE
+{generate} | Out-String | Invoke-Expression"
+                ),
+            ),
+            #[cfg(feature = "zsh")]
+            Self::Zsh => (
+                "zsh",
+                format!(
+                    "mkdir -p ~/.zfunc
+{generate} > ~/.zfunc/{file_name}
+# then in ~/.zshrc, before compinit
+fpath+=~/.zfunc"
+                ),
+            ),
 
-        let correct_order = [
-            ("bash", true),
-            ("carapace", cfg!(feature = "carapace")),
-            ("elvish", true),
-            ("fig", cfg!(feature = "fig")),
-            ("fish", true),
-            ("nushell", cfg!(feature = "nushell")),
-            ("powershell", true),
-            ("zsh", true),
-        ]
-        .iter()
-        .filter(|(_, enabled)| *enabled)
-        .map(|(shell, _)| *shell)
-        .collect::<Vec<_>>();
+            #[cfg(feature = "carapace")]
+            Self::Carapace => (
+                "sh",
+                format!(
+                    "mkdir -p ~/.config/carapace/specs
+{generate} > ~/.config/carapace/specs/{file_name}"
+                ),
+            ),
+            #[cfg(feature = "clink")]
+            Self::Clink => (
+                "cmd",
+                format!(
+                    "{generate} > %LOCALAPPDATA%\\clink\\{file_name}
+# or in a directory added with `clink installscripts`"
+                ),
+            ),
+            #[cfg(feature = "fig")]
+            Self::Fig => (
+                "sh",
+                format!(
+                    "{generate} > {file_name}
+# then build the spec with @withfig/autocomplete-tools"
+                ),
+            ),
+            #[cfg(feature = "man")]
+            Self::Man => (
+                "sh",
+                format!(
+                    "mkdir -p ~/.local/share/man/man1
+{generate} > ~/.local/share/man/man1/{file_name}"
+                ),
+            ),
+            #[cfg(feature = "murex")]
+            Self::Murex => (
+                "murex",
+                format!(
+                    "{generate} > ~/.{file_name}
+# then in ~/.murex_profile
+source ~/.{file_name}"
+                ),
+            ),
+            #[cfg(feature = "nushell")]
+            Self::Nu => (
+                "nushell",
+                format!(
+                    "mkdir ~/.config/nushell/completions
+{generate} | save --force ~/.config/nushell/completions/{file_name}
+# then in config.nu
+source ~/.config/nushell/completions/{file_name}"
+                ),
+            ),
+            #[cfg(feature = "tcsh")]
+            Self::Tcsh => (
+                "tcsh",
+                format!(
+                    "{generate} > ~/.{file_name}
+# then in ~/.tcshrc
+source ~/.{file_name}"
+                ),
+            ),
+            #[cfg(feature = "xonsh")]
+            Self::Xonsh => (
+                "xonsh",
+                format!(
+                    "mkdir -p ~/.config/xonsh/rc.d
+{generate} > ~/.config/xonsh/rc.d/{file_name}"
+                ),
+            ),
+        };
 
-        assert_eq!(names, correct_order);
+        (language, steps)
+    }
+
+    /// Whether users install the completions by evaluating the `completions` subcommand's output
+    /// from their startup file or by saving them to a file, e.g. to print the right instructions.
+    ///
+    /// Shells which can do both, like bash and zsh, are [`InstallMethod::Eval`], as that needs no
+    /// file to be kept up to date, and [`Self::install_instructions()`] shows both for them. fish
+    /// is [`InstallMethod::File`], as it loads the saved file by itself when the command is first
+    /// completed. [`Self::source_hint()`] is the one-liner to evaluate for the former, and sources
+    /// the saved file for the others where the shell needs it.
+    #[must_use]
+    pub const fn install_method(&self) -> InstallMethod {
+        match self {
+            #[cfg(feature = "bash")]
+            Self::Bash => InstallMethod::Eval,
+            #[cfg(feature = "elvish")]
+            Self::Elvish => InstallMethod::Eval,
+            #[cfg(feature = "fish")]
+            Self::Fish => InstallMethod::File,
+            #[cfg(feature = "powershell")]
+            Self::PowerShell => InstallMethod::Eval,
+            #[cfg(feature = "zsh")]
+            Self::Zsh => InstallMethod::Eval,
+
+            #[cfg(feature = "carapace")]
+            Self::Carapace => InstallMethod::File,
+            #[cfg(feature = "clink")]
+            Self::Clink => InstallMethod::File,
+            #[cfg(feature = "fig")]
+            Self::Fig => InstallMethod::File,
+            #[cfg(feature = "man")]
+            Self::Man => InstallMethod::File,
+            #[cfg(feature = "murex")]
+            Self::Murex => InstallMethod::File,
+            #[cfg(feature = "nushell")]
+            Self::Nu => InstallMethod::File,
+            #[cfg(feature = "tcsh")]
+            Self::Tcsh => InstallMethod::Eval,
+            #[cfg(feature = "xonsh")]
+            Self::Xonsh => InstallMethod::Eval,
+        }
+    }
+
+    /// Whether [`clap_complete`]'s dynamic completions, which call the binary to complete values
+    /// computed at runtime, support the shell, rather than only the static completions generated
+    /// by [`Self::generate()`].
+    ///
+    /// With the `dynamic` feature, [`dynamic::write_registration()`] writes the script registering
+    /// them for these shells.
+    #[must_use]
+    pub const fn supports_dynamic(&self) -> bool {
+        match self {
+            #[cfg(feature = "bash")]
+            Self::Bash => true,
+            #[cfg(feature = "elvish")]
+            Self::Elvish => true,
+            #[cfg(feature = "fish")]
+            Self::Fish => true,
+            #[cfg(feature = "powershell")]
+            Self::PowerShell => true,
+            #[cfg(feature = "zsh")]
+            Self::Zsh => true,
+
+            #[cfg(feature = "carapace")]
+            Self::Carapace => false,
+            #[cfg(feature = "clink")]
+            Self::Clink => false,
+            #[cfg(feature = "fig")]
+            Self::Fig => false,
+            #[cfg(feature = "man")]
+            Self::Man => false,
+            #[cfg(feature = "murex")]
+            Self::Murex => false,
+            #[cfg(feature = "nushell")]
+            Self::Nu => false,
+            #[cfg(feature = "tcsh")]
+            Self::Tcsh => false,
+            #[cfg(feature = "xonsh")]
+            Self::Xonsh => false,
+        }
+    }
+
+    /// Whether the completions complete the visible aliases of subcommands and options, set with
+    /// [`clap::Command::visible_alias()`] and [`clap::Arg::visible_alias()`], like their names.
+    ///
+    /// Every shell does, [`Shell::Nu`] by repeating the externs of aliased subcommands. The man
+    /// page doesn't list them, as it documents the command rather than completing it. Hidden
+    /// aliases are left out unless [`GenerateOptions::include_hidden()`] is set.
+    #[must_use]
+    pub const fn includes_aliases(&self) -> bool {
+        match self {
+            #[cfg(feature = "bash")]
+            Self::Bash => true,
+            #[cfg(feature = "elvish")]
+            Self::Elvish => true,
+            #[cfg(feature = "fish")]
+            Self::Fish => true,
+            #[cfg(feature = "powershell")]
+            Self::PowerShell => true,
+            #[cfg(feature = "zsh")]
+            Self::Zsh => true,
+
+            #[cfg(feature = "carapace")]
+            Self::Carapace => true,
+            #[cfg(feature = "clink")]
+            Self::Clink => true,
+            #[cfg(feature = "fig")]
+            Self::Fig => true,
+            #[cfg(feature = "man")]
+            Self::Man => false,
+            #[cfg(feature = "murex")]
+            Self::Murex => true,
+            #[cfg(feature = "nushell")]
+            Self::Nu => true,
+            #[cfg(feature = "tcsh")]
+            Self::Tcsh => true,
+            #[cfg(feature = "xonsh")]
+            Self::Xonsh => true,
+        }
+    }
+
+    /// A line for the shell's startup file which loads the completions, e.g.
+    /// `source ~/.config/nushell/completions/cli.nu` for [`Shell::Nu`].
+    ///
+    /// Like [`Self::setup_doc()`], it assumes the completions are generated by a `completions`
+    /// subcommand of `bin_name`. Shells which can't load the completions from the subcommand's
+    /// output use the file [`Self::setup_doc()`] saves them to.
+    /// Returns `None` for the formats that aren't loaded by a shell, and for fish, which loads the
+    /// saved file by itself.
+    #[must_use]
+    #[cfg_attr(
+        not(any(
+            feature = "bash",
+            feature = "elvish",
+            feature = "powershell",
+            feature = "tcsh",
+            feature = "xonsh",
+            feature = "zsh",
+        )),
+        allow(unused_variables)
+    )]
+    pub fn source_hint(&self, bin_name: &str) -> Option<String> {
+        let generate = format!("{bin_name} completions {}", self.canonical_name());
+        match self {
+            #[cfg(feature = "bash")]
+            Self::Bash => Some(format!("eval \"$({generate})\"")),
+            #[cfg(feature = "elvish")]
+            Self::Elvish => Some(format!("eval ({generate} | slurp)")),
+            #[cfg(feature = "fish")]
+            Self::Fish => None,
+            #[cfg(feature = "powershell")]
+            Self::PowerShell => Some(format!("{generate} | Out-String | Invoke-Expression")),
+            #[cfg(feature = "zsh")]
+            Self::Zsh => Some(format!("source <({generate})")),
+
+            #[cfg(feature = "carapace")]
+            Self::Carapace => None,
+            #[cfg(feature = "clink")]
+            Self::Clink => None,
+            #[cfg(feature = "fig")]
+            Self::Fig => None,
+            #[cfg(feature = "man")]
+            Self::Man => None,
+            #[cfg(feature = "murex")]
+            Self::Murex => Some(format!("source ~/.{}", self.file_name(bin_name))),
+            #[cfg(feature = "nushell")]
+            Self::Nu => Some(format!(
+                "source ~/.config/nushell/completions/{}",
+                self.file_name(bin_name)
+            )),
+            #[cfg(feature = "tcsh")]
+            Self::Tcsh => Some(format!("eval \"`{generate}`\"")),
+            #[cfg(feature = "xonsh")]
+            Self::Xonsh => Some(format!("exec($({generate}))")),
+        }
+    }
+
+    /// See [`clap_complete::generate()`].
+    ///
+    /// The `command`'s bin name is used as the completion's bin name.
+    /// If the `command`'s bin name is not set, it will be set to the `command`'s name.
+    /// Any directories are stripped, so a bin name like `/usr/local/bin/app` completes `app`.
+    ///
+    /// Settings that only affect `--help`, like [`clap::Command::flatten_help()`], don't affect the
+    /// completions, nested subcommands are still completed one level at a time. Neither do settings
+    /// that only affect parsing, like [`clap::Command::ignore_errors()`].
+    ///
+    /// In debug builds this panics if the bin name would be empty, as the completions could never
+    /// be triggered.
+    pub fn generate(self, command: &mut clap::Command, buffer: &mut dyn std::io::Write) {
+        generate_with_backend(self, command, buffer);
+    }
+
+    /// Like [`Self::generate()`] but writes through a [`std::io::BufWriter`], for unbuffered
+    /// writers like a [`std::fs::File`].
+    ///
+    /// The built-in generators mostly write their script in a few large pieces, so this makes no
+    /// measurable difference for them (see `benches/generate.rs`). It matters for
+    /// [`CompletionBackend`]s which write many small pieces.
+    pub fn generate_buffered(self, command: &mut clap::Command, buffer: &mut dyn std::io::Write) {
+        let mut buffer = std::io::BufWriter::new(buffer);
+        self.generate(command, &mut buffer);
+        std::io::Write::flush(&mut buffer).expect("failed to write completion file");
+    }
+
+    /// Like [`Self::generate()`] but returns write errors instead of panicking.
+    ///
+    /// The completions are generated into memory first, as the generators panic when writing
+    /// fails, so nothing is written to `buffer` until they're complete.
+    ///
+    /// A [`std::io::ErrorKind::BrokenPipe`] isn't an error, so output piped into e.g. `head`
+    /// which exits early stops quietly.
+    ///
+    /// # Errors
+    ///
+    /// If writing to `buffer` fails for another reason, e.g. because the disk is full.
+    pub fn generate_checked(
+        self,
+        command: &mut clap::Command,
+        buffer: &mut dyn std::io::Write,
+    ) -> Result<(), std::io::Error> {
+        let mut script = Vec::new();
+        self.generate(command, &mut script);
+        match buffer.write_all(&script).and_then(|()| buffer.flush()) {
+            Err(error) if error.kind() == std::io::ErrorKind::BrokenPipe => Ok(()),
+            result => result,
+        }
+    }
+
+    /// Like [`Self::generate()`] but the completions are for `bin_name` rather than the
+    /// `command`'s bin name, e.g. for a tool which is also invoked through symlinks under other
+    /// names.
+    ///
+    /// Like the `command`'s bin name in [`Self::generate()`], any directories are stripped from
+    /// `bin_name`, and in debug builds this panics if it would be empty.
+    pub fn generate_with_bin_name(
+        self,
+        command: &mut clap::Command,
+        bin_name: impl Into<String>,
+        buffer: &mut dyn std::io::Write,
+    ) {
+        let bin_name = without_dirs(&bin_name.into()).to_owned();
+        debug_assert!(!bin_name.is_empty(), "the bin name is empty");
+        clap_complete::generate(self, command, bin_name, buffer);
+    }
+
+    /// Like [`Self::generate()`] but the completions are for the subcommand at `path`, e.g.
+    /// `["remote", "add"]`, as a command of its own, so plugins of a `git`-style multiplexer can
+    /// ship their own completions.
+    ///
+    /// The bin name is the `command`'s joined to the path with dashes, e.g. `app-remote-add`, the
+    /// name such a plugin's binary usually has. Global arguments of the parent commands are kept.
+    ///
+    /// # Errors
+    ///
+    /// [`std::io::ErrorKind::InvalidInput`] if there's no subcommand at `path` or the `command` has
+    /// an empty bin name. If writing to `buffer` fails, its error is returned as is.
+    pub fn generate_subcommand(
+        self,
+        command: &mut clap::Command,
+        path: &[&str],
+        buffer: &mut dyn std::io::Write,
+    ) -> Result<(), std::io::Error> {
+        let mut bin_name = checked_bin_name(command)?;
+        let mut parent_path = bin_name.clone();
+        let mut subcommand = command.clone();
+        let mut globals = Vec::new();
+        for name in path {
+            globals.extend(
+                subcommand
+                    .get_arguments()
+                    .filter(|arg| arg.is_global_set())
+                    .cloned(),
+            );
+            subcommand = subcommand
+                .find_subcommand(name)
+                .ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        format!("`{parent_path}` has no subcommand `{name}`"),
+                    )
+                })?
+                .clone();
+            bin_name.push('-');
+            bin_name.push_str(subcommand.get_name());
+            parent_path.push(' ');
+            parent_path.push_str(subcommand.get_name());
+        }
+        for global in globals {
+            if !subcommand
+                .get_arguments()
+                .any(|arg| arg.get_id() == global.get_id())
+            {
+                subcommand = subcommand.arg(global);
+            }
+        }
+
+        // Renamed too for the specs and man page, which use the name rather than the bin name
+        let mut subcommand = subcommand.name(leak(&bin_name));
+        let mut script = Vec::new();
+        clap_complete::generate(self, &mut subcommand, bin_name, &mut script);
+        buffer.write_all(&script)
+    }
+
+    /// Like [`Self::generate()`] but customised by `options`.
+    ///
+    /// The completions are generated from a copy of the `command`, so it isn't modified.
+    pub fn generate_with_options(
+        self,
+        command: &mut clap::Command,
+        options: GenerateOptions,
+        buffer: &mut dyn std::io::Write,
+    ) {
+        let mut command = options.prepare(self, command);
+        let mut script = Vec::new();
+        self.generate(&mut command, &mut script);
+        buffer
+            .write_all(&options.finish(self, &command, script))
+            .expect("failed to write completion file");
+    }
+
+    /// Like [`Self::generate_to()`] but with `options`, see [`Self::generate_with_options()`].
+    ///
+    /// The file is named by [`GenerateOptions::file_name()`].
+    ///
+    /// # Errors
+    ///
+    /// See [`Self::generate_to()`].
+    pub fn generate_to_with_options<S>(
+        self,
+        command: &mut clap::Command,
+        options: GenerateOptions,
+        out_dir: S,
+    ) -> Result<PathBuf, std::io::Error>
+    where
+        S: Into<OsString>,
+    {
+        let bin_name = checked_bin_name(command)?;
+        let out_dir = PathBuf::from(out_dir.into());
+        std::fs::create_dir_all(&out_dir)?;
+        let path = out_dir.join(options.file_name(self, &bin_name));
+        let mut file = std::fs::File::create(&path)?;
+        self.generate_with_options(command, options, &mut file);
+        Ok(path)
+    }
+
+    /// Like [`Self::generate()`] but starts with `header` as a comment, e.g. to note which version
+    /// of a CLI generated the completions.
+    ///
+    /// Each line of `header` is commented out the way the shell expects, and lines which have to
+    /// come first, like zsh's `#compdef`, stay first.
+    pub fn generate_with_header(
+        self,
+        command: &mut clap::Command,
+        header: &str,
+        buffer: &mut dyn std::io::Write,
+    ) {
+        let script = self.generate_to_string(command);
+        buffer
+            .write_all(header::insert(self, &script, header).as_bytes())
+            .expect("failed to write completion file");
+    }
+
+    /// See [`clap_complete::generate_to()`].
+    ///
+    /// The `command`'s bin name is used as the completion's bin name.
+    /// If the `command`'s bin name is not set, it will be set to the `command`'s name.
+    /// Any directories are stripped, so a bin name like `/usr/local/bin/app` completes `app`.
+    ///
+    /// `out_dir` is created if it doesn't exist, along with its parents.
+    ///
+    /// # Errors
+    ///
+    /// If the bin name would be empty, as the completions could never be triggered, or if creating
+    /// `out_dir` or writing the file fails.
+    pub fn generate_to<S>(
+        self,
+        command: &mut clap::Command,
+        out_dir: S,
+    ) -> Result<PathBuf, std::io::Error>
+    where
+        S: Into<OsString>,
+    {
+        let bin_name = checked_bin_name(command)?;
+        let out_dir = out_dir.into();
+        std::fs::create_dir_all(&out_dir)?;
+        clap_complete::generate_to(self, command, bin_name, out_dir)
+    }
+
+    /// Generates completions for every shell in [`Self::all()`] into `out_dir`, returning the paths
+    /// in the same order.
+    ///
+    /// This suits a `build.rs` generating completions for packaging into `OUT_DIR`, where the
+    /// shells are the ones enabled in `[build-dependencies]`. The files are overwritten on every
+    /// build, and the paths can be printed with `cargo:warning=` for packaging scripts to find, see
+    /// `examples/build.rs`.
+    ///
+    /// ```no_run
+    /// // build.rs
+    /// let out_dir = std::env::var_os("OUT_DIR").unwrap();
+    /// let mut command = clap::Command::new("cli");
+    /// for path in clap_complete_command::Shell::generate_all_to(&mut command, out_dir)? {
+    ///     println!("cargo:warning=generated {}", path.display());
+    /// }
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// See [`Self::generate_to()`], generation stops at the first error.
+    pub fn generate_all_to<S>(
+        command: &mut clap::Command,
+        out_dir: S,
+    ) -> Result<Vec<PathBuf>, std::io::Error>
+    where
+        S: Into<OsString>,
+    {
+        let out_dir = out_dir.into();
+        Self::all()
+            .iter()
+            .map(|shell| shell.generate_to(command, &out_dir))
+            .collect()
+    }
+
+    /// Like [`Self::generate_all_to()`] but each shell's completions are written to a directory
+    /// named after the shell in `base_dir`, which is created if needed, e.g. `bash/cli.bash` and
+    /// `fish/cli.fish`.
+    ///
+    /// # Errors
+    ///
+    /// See [`Self::generate_to()`], generation stops at the first error.
+    pub fn generate_all_to_tree<S>(
+        command: &mut clap::Command,
+        base_dir: S,
+    ) -> Result<Vec<PathBuf>, std::io::Error>
+    where
+        S: Into<OsString>,
+    {
+        let base_dir = PathBuf::from(base_dir.into());
+        Self::all()
+            .iter()
+            .map(|shell| shell.generate_to(command, base_dir.join(shell.canonical_name())))
+            .collect()
+    }
+
+    /// Generates completions for every shell in this build into `out_dir` concurrently, one thread
+    /// per shell.
+    ///
+    /// This is only worthwhile for large commands on machines with spare cores, otherwise the
+    /// threads' overhead makes it slower than generating each shell in turn.
+    /// Each thread generates from its own clone of `command`, so `command` is left untouched.
+    /// The paths are returned in the same order as [`Self::variants_for_build()`].
+    ///
+    /// # Errors
+    ///
+    /// The first error in that order is returned after all threads have finished.
+    #[cfg(feature = "parallel")]
+    pub fn generate_all_to_parallel<S>(
+        command: &clap::Command,
+        out_dir: S,
+    ) -> Result<Vec<PathBuf>, std::io::Error>
+    where
+        S: Into<OsString>,
+    {
+        let out_dir = out_dir.into();
+        std::thread::scope(|scope| {
+            let handles = Self::variants_for_build()
+                .iter()
+                .map(|shell| {
+                    let mut command = command.clone();
+                    let out_dir = out_dir.clone();
+                    scope.spawn(move || shell.generate_to(&mut command, out_dir))
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|payload| std::panic::resume_unwind(payload))
+                })
+                .collect()
+        })
+    }
+
+    /// Like [`Self::generate()`] but returns the completions' bytes, e.g. to compare a checksum
+    /// before rewriting a file.
+    #[must_use]
+    pub fn generate_to_vec(self, command: &mut clap::Command) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        self.generate(command, &mut buffer);
+        buffer
+    }
+
+    /// Like [`Self::generate()`] but returns the completions as a [`String`].
+    ///
+    /// # Panics
+    ///
+    /// If the generated completions aren't valid UTF-8, which none of the shells generate, see
+    /// [`Self::try_generate_to_string()`].
+    #[must_use]
+    pub fn generate_to_string(self, command: &mut clap::Command) -> String {
+        self.try_generate_to_string(command)
+            .expect("completions should be valid UTF-8")
+    }
+
+    /// Like [`Self::generate()`] but returns the completions as a [`String`].
+    ///
+    /// # Errors
+    ///
+    /// If the generated completions aren't valid UTF-8, rather than converting them lossily.
+    /// [`Self::generate_to_vec()`] returns the raw bytes instead.
+    pub fn try_generate_to_string(
+        self,
+        command: &mut clap::Command,
+    ) -> Result<String, std::string::FromUtf8Error> {
+        generate_to_string(self, command)
+    }
+
+    /// Like [`Self::generate_to_string()`] but smoke tests the completions, for checking them in a
+    /// CLI's own tests: they mustn't be empty and must mention the bin name and each visible
+    /// top-level subcommand.
+    ///
+    /// This doesn't parse the completions, so it can't tell if the shell would load them.
+    ///
+    /// ```
+    /// use clap_complete_command::Shell;
+    ///
+    /// let mut command = clap::Command::new("cli").subcommand(clap::Command::new("serve"));
+    /// for shell in Shell::all() {
+    ///     shell.generate_and_check(&mut command).unwrap();
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A [`CheckError`] describing the first check which failed.
+    pub fn generate_and_check(self, command: &mut clap::Command) -> Result<String, CheckError> {
+        let completions = self
+            .try_generate_to_string(command)
+            .map_err(CheckError::InvalidUtf8)?;
+        check::check(&completions, command, &bin_name(command))?;
+        Ok(completions)
+    }
+
+    /// Like [`Self::generate()`] but the args named by the keys of `files` (their ids) complete the
+    /// values listed in the files, e.g. known hosts from a config file, so they're baked into the
+    /// completions.
+    ///
+    /// The files have one value per line, blank lines are ignored. The values replace the args'
+    /// possible values in every subcommand, the `command` itself isn't modified.
+    ///
+    /// # Errors
+    ///
+    /// If a file can't be read, or if no arg has the name it's given for.
+    pub fn generate_with_value_files(
+        self,
+        command: &mut clap::Command,
+        files: HashMap<String, PathBuf>,
+        buffer: &mut dyn std::io::Write,
+    ) -> Result<(), std::io::Error> {
+        let mut command = command.clone();
+        for (name, path) in files {
+            if !has_arg(&command, &name) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("no arg is named `{name}`"),
+                ));
+            }
+            let values = std::fs::read_to_string(path)?
+                .lines()
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_owned)
+                .collect::<Vec<_>>();
+            command = with_possible_values(command, &name, &values);
+        }
+        self.generate(&mut command, buffer);
+        Ok(())
+    }
+
+    /// Roughly estimates how much loading the completions of `command` adds to shell startup, from
+    /// their size and their number of subcommands and args.
+    ///
+    /// The completions are generated to be measured, so this costs as much as [`Self::generate()`].
+    /// [`StartupCost::Large`] completions should be lazy-loaded rather than sourced at startup.
+    #[must_use]
+    pub fn startup_cost_estimate(self, command: &clap::Command) -> StartupCost {
+        let mut command = command.clone();
+        let mut script = Vec::new();
+        self.generate(&mut command, &mut script);
+        startup_cost::classify(script.len(), startup_cost::count_items(&command))
+    }
+
+    /// Generates the completions as a Rust `const` item named `const_name`, e.g.
+    /// `pub const BASH_COMPLETIONS: &str = "...";`, so a build script can write them into a `.rs`
+    /// file and the binary can embed them without generating them at runtime.
+    ///
+    /// `const_name` must be a valid Rust identifier, it's used as is.
+    ///
+    /// # Panics
+    ///
+    /// See [`Self::generate_to_string()`].
+    #[must_use]
+    pub fn generate_rust_literal(self, const_name: &str, command: &mut clap::Command) -> String {
+        let script = self.generate_to_string(command);
+        // `str`'s `Debug` output is an escaped string literal
+        format!("pub const {const_name}: &str = {script:?};\n")
+    }
+
+    /// Like [`Self::generate_to()`] but the completion file is named `file_name` when it's set,
+    /// instead of the shell's conventional file name.
+    ///
+    /// # Errors
+    ///
+    /// See [`Self::generate_to()`].
+    pub fn generate_to_with<S>(
+        self,
+        command: &mut clap::Command,
+        out_dir: S,
+        file_name: Option<String>,
+    ) -> Result<PathBuf, std::io::Error>
+    where
+        S: Into<OsString>,
+    {
+        let Some(file_name) = file_name else {
+            return self.generate_to(command, out_dir);
+        };
+
+        let path = PathBuf::from(out_dir.into()).join(file_name);
+        self.generate_to_path(command, &path)?;
+        Ok(path)
+    }
+
+    /// Like [`Self::generate_to()`] but the completion file is named by `name_fn`, which is given
+    /// the bin name, e.g. for a packaging scheme like `_cli.zsh` rather than the shell's
+    /// conventional file name, see [`Self::file_name()`].
+    ///
+    /// # Errors
+    ///
+    /// See [`Self::generate_to()`].
+    pub fn generate_to_with_name<S, F>(
+        self,
+        command: &mut clap::Command,
+        out_dir: S,
+        name_fn: F,
+    ) -> Result<PathBuf, std::io::Error>
+    where
+        S: Into<OsString>,
+        F: FnOnce(&str) -> String,
+    {
+        let file_name = name_fn(&checked_bin_name(command)?);
+        self.generate_to_with(command, out_dir, Some(file_name))
+    }
+
+    /// Like [`Self::generate_to()`] but an existing completion file is handled according to
+    /// `policy`, e.g. to not replace a file the user has customised when regenerating completions.
+    ///
+    /// Returns the path of the file if it was written, or `None` if it already existed and
+    /// `policy` is [`OverwritePolicy::SkipIfExists`].
+    ///
+    /// # Errors
+    ///
+    /// [`std::io::ErrorKind::AlreadyExists`] if the file already exists and `policy` is
+    /// [`OverwritePolicy::ErrorIfExists`], or see [`Self::generate_to()`].
+    pub fn generate_to_with_policy<S>(
+        self,
+        command: &mut clap::Command,
+        out_dir: S,
+        policy: OverwritePolicy,
+    ) -> Result<Option<PathBuf>, std::io::Error>
+    where
+        S: Into<OsString>,
+    {
+        if policy == OverwritePolicy::Overwrite {
+            return self.generate_to(command, out_dir).map(Some);
+        }
+
+        let bin_name = checked_bin_name(command)?;
+        let out_dir = PathBuf::from(out_dir.into());
+        std::fs::create_dir_all(&out_dir)?;
+        let path = out_dir.join(self.file_name(&bin_name));
+        // Creating the file only if it's new doesn't race with another process creating it
+        let mut file = match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+        {
+            Ok(file) => file,
+            Err(error)
+                if error.kind() == std::io::ErrorKind::AlreadyExists
+                    && policy == OverwritePolicy::SkipIfExists =>
+            {
+                return Ok(None);
+            }
+            Err(error) => return Err(error),
+        };
+        clap_complete::generate(self, command, bin_name, &mut file);
+        Ok(Some(path))
+    }
+
+    /// Like [`Self::generate_to()`] but writes the completions to `path` exactly, creating or
+    /// truncating the file, instead of to the shell's conventional file name in a directory.
+    /// Its directory is created if it doesn't exist, along with its parents.
+    ///
+    /// # Errors
+    ///
+    /// See [`Self::generate_to()`].
+    pub fn generate_to_path<P: AsRef<Path>>(
+        self,
+        command: &mut clap::Command,
+        path: P,
+    ) -> Result<(), std::io::Error> {
+        let bin_name = checked_bin_name(command)?;
+        if let Some(dir) = path.as_ref().parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut file = std::fs::File::create(path)?;
+        clap_complete::generate(self, command, bin_name, &mut file);
+        Ok(())
+    }
+
+    /// The conventional per-user directory the shell loads completions from, without creating it,
+    /// e.g. to tell users where to put the file generated by [`Self::generate_to()`].
+    ///
+    /// `$XDG_DATA_HOME` and `$XDG_CONFIG_HOME` are respected by the shells reading them, otherwise
+    /// the directories are under the home directory. The locations are the same as in
+    /// [`Self::setup_doc()`], which also shows the startup file changes some shells still need,
+    /// e.g. adding `~/.zfunc` to zsh's `fpath`.
+    ///
+    /// Returns [`None`] for shells which don't load completions from a directory, like elvish,
+    /// PowerShell, tcsh and fig, or if the home directory is unknown.
+    #[must_use]
+    pub fn completion_dir(&self) -> Option<PathBuf> {
+        let (base, dir) = self.user_dir()?;
+        Some(base.resolve()?.join(dir))
+    }
+
+    /// The base directory and the path within it of [`Self::completion_dir()`]
+    const fn user_dir(self) -> Option<(BaseDir, &'static str)> {
+        match self {
+            #[cfg(feature = "bash")]
+            Self::Bash => Some((BaseDir::Data, "bash-completion/completions")),
+            #[cfg(feature = "fish")]
+            Self::Fish => Some((BaseDir::Config, "fish/completions")),
+            #[cfg(feature = "zsh")]
+            Self::Zsh => Some((BaseDir::Home, ".zfunc")),
+            #[cfg(feature = "elvish")]
+            Self::Elvish => None,
+            #[cfg(feature = "powershell")]
+            Self::PowerShell => None,
+
+            #[cfg(feature = "carapace")]
+            Self::Carapace => Some((BaseDir::Config, "carapace/specs")),
+            #[cfg(feature = "clink")]
+            Self::Clink => None,
+            #[cfg(feature = "fig")]
+            Self::Fig => None,
+            #[cfg(feature = "man")]
+            Self::Man => Some((BaseDir::Data, "man/man1")),
+            #[cfg(feature = "murex")]
+            Self::Murex => None,
+            #[cfg(feature = "nushell")]
+            Self::Nu => Some((BaseDir::Config, "nushell/completions")),
+            #[cfg(feature = "tcsh")]
+            Self::Tcsh => None,
+            #[cfg(feature = "xonsh")]
+            Self::Xonsh => Some((BaseDir::Config, "xonsh/rc.d")),
+        }
+    }
+
+    /// Writes the completions to [`Self::completion_dir()`], creating it if needed, and returns the
+    /// path, for a `completions --install` flag.
+    ///
+    /// Bash completions are written in the [`BashForm::LazyLoad`] form, so bash-completion loads
+    /// them the first time the command is completed. PowerShell completions are written next to the
+    /// profile of [`PowerShellEdition::default()`], see [`PowerShellEdition::install()`] to pick
+    /// the edition.
+    ///
+    /// # Errors
+    ///
+    /// [`std::io::ErrorKind::Unsupported`] for shells which don't load completions from a
+    /// directory, like elvish and tcsh, [`std::io::ErrorKind::NotFound`] if the home directory is unknown,
+    /// or see [`Self::generate_to()`].
+    pub fn install(self, command: &mut clap::Command) -> Result<PathBuf, std::io::Error> {
+        #[cfg(feature = "powershell")]
+        if self == Self::PowerShell {
+            return PowerShellEdition::default().install(command);
+        }
+        let (base, subdir) = self.user_dir().ok_or_else(|| unsupported_install(self))?;
+        let dir = base
+            .resolve()
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    "the home directory is unknown",
+                )
+            })?
+            .join(subdir);
+
+        #[cfg(feature = "bash")]
+        if self == Self::Bash {
+            let options = GenerateOptions::new().bash_form(BashForm::LazyLoad);
+            return self.generate_to_with_options(command, options, dir);
+        }
+
+        self.generate_to(command, dir)
+    }
+
+    /// See [`Self::generate_to()`].
+    ///
+    /// `on_write` is called with the path of the completion file once it has been written, e.g. to
+    /// report progress from an installer.
+    pub fn generate_to_with_progress<S, F>(
+        self,
+        command: &mut clap::Command,
+        out_dir: S,
+        mut on_write: F,
+    ) -> Result<PathBuf, std::io::Error>
+    where
+        S: Into<OsString>,
+        F: FnMut(&Path),
+    {
+        let path = self.generate_to(command, out_dir)?;
+        on_write(&path);
+        Ok(path)
+    }
+}
+
+/// The `completions <SHELL>` subcommand of the builder example, see
+/// [`completions_command_named()`].
+///
+/// # Examples
+///
+/// ```
+/// use clap_complete_command::Shell;
+///
+/// let command = clap::Command::new("app").subcommand(clap_complete_command::completions_subcommand());
+///
+/// # #[cfg(feature = "zsh")] {
+/// let matches = command.get_matches_from(["app", "completions", "zsh"]);
+/// let (_, sub_matches) = matches.subcommand().unwrap();
+/// assert_eq!(sub_matches.get_one::<Shell>("shell"), Some(&Shell::Zsh));
+/// # }
+/// ```
+#[must_use]
+pub fn completions_subcommand() -> clap::Command {
+    completions_command_named("completions")
+}
+
+/// [`completions_subcommand()`] which can also be called by each of `aliases`, e.g. `completion`
+/// and `complete`.
+///
+/// The aliases aren't shown in `--help`. `clap` reports the subcommand as `completions` whichever
+/// name was used, so [`run()`] and [`Shell::from_matches()`] handle it the same.
+#[must_use]
+pub fn completions_subcommand_with_aliases(aliases: &[&'static str]) -> clap::Command {
+    completions_subcommand().aliases(aliases.iter().copied())
+}
+
+/// A subcommand called `name` with a required `shell` argument, which can be read with
+/// `get_one::<Shell>("shell")`, and an optional `--output <FILE>` to write the completions to,
+/// both handled by [`run_completions()`].
+///
+/// Its `about` and the argument's `help` can be changed with [`clap::Command::about()`] and
+/// [`clap::Command::mut_arg()`].
+#[must_use]
+pub fn completions_command_named(name: impl Into<clap::builder::Str>) -> clap::Command {
+    clap::Command::new(name)
+        .about("Generate shell completions")
+        .arg(
+            clap::Arg::new("shell")
+                .value_name("SHELL")
+                .help("The shell to generate the completions for")
+                .required(true)
+                .value_parser(clap::builder::EnumValueParser::<Shell>::new()),
+        )
+        .arg(
+            clap::Arg::new("output")
+                .long("output")
+                .short('o')
+                .value_name("FILE")
+                .help("Write the completions to FILE instead of stdout")
+                .value_hint(clap::ValueHint::FilePath)
+                .value_parser(clap::value_parser!(PathBuf)),
+        )
+}
+
+/// Handles the `completions` subcommand, if it's the one `matches` are for, by generating the
+/// completions for its `shell` arg into `writer`, or its `--output` file if it has one.
+///
+/// Returns whether the subcommand was handled, in which case `main` should usually exit. The
+/// subcommand has to be named `completions` and its shell arg `shell`, as in the
+/// [crate examples](crate#examples).
+///
+/// # Errors
+///
+/// If the `completions` subcommand has no `shell`, or see [`Shell::generate_checked()`].
+///
+/// # Examples
+///
+/// ## Derive
+///
+/// ```no_run
+/// use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+///
+/// #[derive(Parser)]
+/// struct Cli {
+///     #[command(subcommand)]
+///     command: Commands,
+/// }
+///
+/// #[derive(Subcommand)]
+/// enum Commands {
+///     Completions {
+///         #[arg(value_enum)]
+///         shell: clap_complete_command::Shell,
+///     },
+///     // ...
+/// }
+///
+/// let matches = Cli::command().get_matches();
+/// if clap_complete_command::run(&matches, &mut Cli::command(), &mut std::io::stdout())? {
+///     return Ok(());
+/// }
+/// let cli = Cli::from_arg_matches(&matches)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// ## Builder
+///
+/// ```no_run
+/// use clap::Command;
+///
+/// fn build_cli() -> Command {
+///     Command::new(env!("CARGO_PKG_NAME")).subcommand(clap_complete_command::completions_subcommand())
+/// }
+///
+/// let matches = build_cli().get_matches();
+/// if clap_complete_command::run(&matches, &mut build_cli(), &mut std::io::stdout())? {
+///     return Ok(());
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn run(
+    matches: &clap::ArgMatches,
+    command: &mut clap::Command,
+    writer: &mut dyn std::io::Write,
+) -> Result<bool, std::io::Error> {
+    let Some(("completions", sub_matches)) = matches.subcommand() else {
+        return Ok(false);
+    };
+
+    let shell = shell_arg(sub_matches)?;
+    match sub_matches.try_get_one::<PathBuf>("output").ok().flatten() {
+        Some(output) => shell.generate_to_path(command, output)?,
+        None => shell.generate_checked(command, writer)?,
+    }
+    Ok(true)
+}
+
+/// Generates the completions selected by the `matches` of [`completions_subcommand()`] for
+/// `command`, writing them to the `--output` file if it's given and stdout otherwise.
+///
+/// The file's directory is created if it doesn't exist, see [`Shell::generate_to_path()`].
+///
+/// # Errors
+///
+/// If `matches` have no `shell`, or see [`Shell::generate_to_path()`] and
+/// [`Shell::generate_checked()`].
+///
+/// # Examples
+///
+/// ```no_run
+/// fn build_cli() -> clap::Command {
+///     clap::Command::new("app").subcommand(clap_complete_command::completions_subcommand())
+/// }
+///
+/// if let Some(("completions", matches)) = build_cli().get_matches().subcommand() {
+///     clap_complete_command::run_completions(matches, &mut build_cli())?;
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn run_completions(
+    matches: &clap::ArgMatches,
+    command: &mut clap::Command,
+) -> Result<(), std::io::Error> {
+    let shell = shell_arg(matches)?;
+    match matches.try_get_one::<PathBuf>("output").ok().flatten() {
+        Some(output) => shell.generate_to_path(command, output),
+        None => shell.generate_checked(command, &mut std::io::stdout().lock()),
+    }
+}
+
+/// The `shell` arg of the `matches` of [`completions_subcommand()`]
+fn shell_arg(matches: &clap::ArgMatches) -> Result<Shell, std::io::Error> {
+    matches
+        .try_get_one::<Shell>("shell")
+        .ok()
+        .flatten()
+        .copied()
+        .ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "the `completions` subcommand has no `shell`",
+            )
+        })
+}
+
+/// Whether `command` has nothing to complete, i.e. no visible arguments or subcommands besides
+/// `--help`, `--version` and the `help` subcommand, so every shell's completions would be empty.
+///
+/// The completions can still be generated, this is for telling users why they don't do anything.
+#[must_use]
+pub fn would_be_empty(command: &clap::Command) -> bool {
+    let has_arguments = command.get_arguments().any(|arg| {
+        !arg.is_hide_set()
+            && !matches!(
+                arg.get_action(),
+                clap::ArgAction::Help
+                    | clap::ArgAction::HelpShort
+                    | clap::ArgAction::HelpLong
+                    | clap::ArgAction::Version
+            )
+    });
+    let has_subcommands = command
+        .get_subcommands()
+        .any(|subcommand| !subcommand.is_hide_set() && subcommand.get_name() != "help");
+    !has_arguments && !has_subcommands
+}
+
+/// The names and visible aliases of `command`'s subcommands which its completions offer, e.g. to
+/// tell users why a subcommand isn't completed.
+///
+/// Subcommands with [`clap::Command::hide()`] and hidden aliases are left out, like every shell's
+/// completions leave them out unless [`GenerateOptions::include_hidden()`] is set. The `help`
+/// subcommand which clap adds is included unless it's disabled.
+#[must_use]
+pub fn completable_subcommands(command: &clap::Command) -> Vec<String> {
+    let mut command = command.clone();
+    command.build();
+    command
+        .get_subcommands()
+        .filter(|subcommand| !subcommand.is_hide_set())
+        .flat_map(|subcommand| {
+            std::iter::once(subcommand.get_name()).chain(subcommand.get_visible_aliases())
+        })
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Where a per-user completion directory is, see [`Shell::completion_dir()`]
+#[derive(Clone, Copy)]
+enum BaseDir {
+    #[cfg_attr(not(feature = "zsh"), allow(dead_code))]
+    Home,
+    /// `$XDG_CONFIG_HOME`, falling back to `~/.config`
+    Config,
+    /// `$XDG_DATA_HOME`, falling back to `~/.local/share`
+    #[cfg_attr(not(any(feature = "bash", feature = "man")), allow(dead_code))]
+    Data,
+}
+
+impl BaseDir {
+    fn resolve(self) -> Option<PathBuf> {
+        let (var, default) = match self {
+            Self::Home => return home_dir(),
+            Self::Config => ("XDG_CONFIG_HOME", ".config"),
+            Self::Data => ("XDG_DATA_HOME", ".local/share"),
+        };
+        // Relative paths are invalid and must be ignored according to the XDG base directory spec
+        std::env::var_os(var)
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| Some(home_dir()?.join(default)))
+    }
+}
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+}
+
+/// `value` as a `&'static str`, the only strings clap's builders take without its `string`
+/// feature
+///
+/// The string is leaked, which is fine for the names copied into the commands completions are
+/// generated from, usually once per process.
+fn leak(value: &str) -> &'static str {
+    Box::leak(value.to_owned().into_boxed_str())
+}
+
+/// `text` indented by four spaces, like a code block in a terminal
+fn indent(text: &str) -> String {
+    text.lines()
+        .map(|line| format!("    {line}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn unsupported_install(shell: Shell) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        format!("{shell} doesn't load completions from a per-user directory"),
+    )
+}
+
+const EMPTY_BIN_NAME: &str = "the command has neither a bin name nor a name";
+
+/// The `command`'s bin name, falling back to the `command`'s name, without any directories
+fn bin_name(command: &clap::Command) -> String {
+    without_dirs(command.get_bin_name().unwrap_or_else(|| command.get_name())).to_owned()
+}
+
+/// `bin_name` without any directories, as shells complete commands by the name they're run as,
+/// not the path they're installed at
+fn without_dirs(bin_name: &str) -> &str {
+    bin_name
+        .rsplit(std::path::is_separator)
+        .next()
+        .unwrap_or(bin_name)
+}
+
+/// [`bin_name()`] which errors if it's empty
+fn checked_bin_name(command: &clap::Command) -> Result<String, std::io::Error> {
+    let bin_name = bin_name(command);
+    if bin_name.is_empty() {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            EMPTY_BIN_NAME,
+        ))
+    } else {
+        Ok(bin_name)
+    }
+}
+
+/// Whether `command` or any of its subcommands has an arg with the id `name`
+fn has_arg(command: &clap::Command, name: &str) -> bool {
+    command.get_arguments().any(|arg| arg.get_id() == name)
+        || command
+            .get_subcommands()
+            .any(|subcommand| has_arg(subcommand, name))
+}
+
+/// Makes the args with the id `name` in `command` and its subcommands complete `values`
+fn with_possible_values(command: clap::Command, name: &str, values: &[String]) -> clap::Command {
+    command
+        .mut_args(|arg| {
+            if arg.get_id() == name {
+                let values = values.iter().map(|value| leak(value));
+                arg.value_parser(clap::builder::PossibleValuesParser::new(values))
+            } else {
+                arg
+            }
+        })
+        .mut_subcommands(|subcommand| with_possible_values(subcommand, name, values))
+}
+
+fn generate_to_string<G>(
+    generator: G,
+    command: &mut clap::Command,
+) -> Result<String, std::string::FromUtf8Error>
+where
+    G: clap_complete::Generator,
+{
+    let bin_name = bin_name(command);
+    let mut buffer = Vec::new();
+    clap_complete::generate(generator, command, bin_name, &mut buffer);
+    String::from_utf8(buffer)
+}
+
+// Hand-rolled to avoid depending on Clap's `derive` feature
+impl ValueEnum for Shell {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[
+            #[cfg(feature = "bash")]
+            Self::Bash,
+            #[cfg(feature = "carapace")]
+            Self::Carapace,
+            #[cfg(feature = "clink")]
+            Self::Clink,
+            #[cfg(feature = "elvish")]
+            Self::Elvish,
+            #[cfg(feature = "fig")]
+            Self::Fig,
+            #[cfg(feature = "fish")]
+            Self::Fish,
+            #[cfg(feature = "man")]
+            Self::Man,
+            #[cfg(feature = "murex")]
+            Self::Murex,
+            #[cfg(feature = "nushell")]
+            Self::Nu,
+            #[cfg(feature = "powershell")]
+            Self::PowerShell,
+            #[cfg(feature = "tcsh")]
+            Self::Tcsh,
+            #[cfg(feature = "xonsh")]
+            Self::Xonsh,
+            #[cfg(feature = "zsh")]
+            Self::Zsh,
+        ]
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        Some(
+            clap::builder::PossibleValue::new(self.canonical_name())
+                .aliases(self.aliases().iter().copied())
+                .help(self.description()),
+        )
+    }
+}
+
+/// See [`Shell::value_parser()`]
+#[derive(Clone)]
+struct IgnoreCaseValueParser;
+
+impl clap::builder::TypedValueParser for IgnoreCaseValueParser {
+    type Value = Shell;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        match value
+            .to_str()
+            .and_then(|value| Shell::from_str(value, true).ok())
+        {
+            Some(shell) => Ok(shell),
+            None => clap::builder::EnumValueParser::<Shell>::new().parse_ref(cmd, arg, value),
+        }
+    }
+
+    fn possible_values(
+        &self,
+    ) -> Option<Box<dyn Iterator<Item = clap::builder::PossibleValue> + '_>> {
+        Some(Box::new(
+            Shell::value_variants()
+                .iter()
+                .filter_map(ValueEnum::to_possible_value),
+        ))
+    }
+}
+
+/// Fails for the shells whose feature is disabled, or which `clap_complete` added and this crate
+/// doesn't support yet, as [`clap_complete::Shell`] is `#[non_exhaustive]`.
+impl TryFrom<clap_complete::Shell> for Shell {
+    type Error = UnavailableShellError;
+
+    fn try_from(shell: clap_complete::Shell) -> Result<Self, Self::Error> {
+        match shell {
+            #[cfg(feature = "bash")]
+            clap_complete::Shell::Bash => Ok(Self::Bash),
+            #[cfg(feature = "elvish")]
+            clap_complete::Shell::Elvish => Ok(Self::Elvish),
+            #[cfg(feature = "fish")]
+            clap_complete::Shell::Fish => Ok(Self::Fish),
+            #[cfg(feature = "powershell")]
+            clap_complete::Shell::PowerShell => Ok(Self::PowerShell),
+            #[cfg(feature = "zsh")]
+            clap_complete::Shell::Zsh => Ok(Self::Zsh),
+            _ => Err(UnavailableShellError::new(shell)),
+        }
+    }
+}
+
+/// Fails for the shells [`clap_complete`] doesn't generate, with the shell as the error.
+impl TryFrom<Shell> for clap_complete::Shell {
+    type Error = Shell;
+
+    fn try_from(shell: Shell) -> Result<Self, Self::Error> {
+        match shell {
+            #[cfg(feature = "bash")]
+            Shell::Bash => Ok(Self::Bash),
+            #[cfg(feature = "elvish")]
+            Shell::Elvish => Ok(Self::Elvish),
+            #[cfg(feature = "fish")]
+            Shell::Fish => Ok(Self::Fish),
+            #[cfg(feature = "powershell")]
+            Shell::PowerShell => Ok(Self::PowerShell),
+            #[cfg(feature = "zsh")]
+            Shell::Zsh => Ok(Self::Zsh),
+            #[cfg(feature = "carapace")]
+            Shell::Carapace => Err(shell),
+            #[cfg(feature = "clink")]
+            Shell::Clink => Err(shell),
+            #[cfg(feature = "fig")]
+            Shell::Fig => Err(shell),
+            #[cfg(feature = "man")]
+            Shell::Man => Err(shell),
+            #[cfg(feature = "murex")]
+            Shell::Murex => Err(shell),
+            #[cfg(feature = "nushell")]
+            Shell::Nu => Err(shell),
+            #[cfg(feature = "tcsh")]
+            Shell::Tcsh => Err(shell),
+            #[cfg(feature = "xonsh")]
+            Shell::Xonsh => Err(shell),
+        }
+    }
+}
+
+/// A guess at the shell based on the platform the crate is compiled for: [`Shell::PowerShell`] on
+/// Windows and [`Shell::Bash`] elsewhere, or the first shell in [`Shell::all()`] if that one's
+/// feature is disabled.
+///
+/// It doesn't look at the environment, see [`Shell::from_env()`] for detecting the user's shell.
+impl Default for Shell {
+    fn default() -> Self {
+        let platform_shell = if cfg!(windows) { "powershell" } else { "bash" };
+        Self::all()
+            .iter()
+            .find(|shell| shell.canonical_name() == platform_shell)
+            .unwrap_or(&Self::all()[0])
+            .to_owned()
+    }
+}
+
+impl std::fmt::Display for Shell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.canonical_name().fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::ValueEnum;
+
+    fn generate(shell: Shell, command: &mut clap::Command) -> String {
+        let mut buffer = Vec::new();
+        shell.generate(command, &mut buffer);
+        String::from_utf8(buffer).unwrap()
+    }
+
+    /// Runs `f` with the environment variables set, or removed if [`None`], restoring them
+    /// afterwards. Tests reading or writing environment variables must use this so they don't race.
+    fn with_vars<T>(vars: &[(&str, Option<&str>)], f: impl FnOnce() -> T) -> T {
+        static LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+        let _guard = LOCK
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        let originals = vars
+            .iter()
+            .map(|(var, value)| {
+                let original = std::env::var_os(var);
+                match value {
+                    Some(value) => std::env::set_var(var, value),
+                    None => std::env::remove_var(var),
+                }
+                (var, original)
+            })
+            .collect::<Vec<_>>();
+
+        let result = f();
+
+        for (var, original) in originals {
+            match original {
+                Some(original) => std::env::set_var(var, original),
+                None => std::env::remove_var(var),
+            }
+        }
+        result
+    }
+
+    macro_rules! check_shell_value_test {
+        ($test_name:ident, $shell:expr, $value:expr) => {
+            #[test]
+            fn $test_name() {
+                assert_eq!($shell.to_possible_value().unwrap().get_name(), $value);
+            }
+        };
+    }
+
+    #[cfg(feature = "bash")]
+    check_shell_value_test!(test_shell_value_bash, Shell::Bash, "bash");
+    #[cfg(feature = "carapace")]
+    check_shell_value_test!(test_shell_value_carapace, Shell::Carapace, "carapace");
+    #[cfg(feature = "clink")]
+    check_shell_value_test!(test_shell_value_clink, Shell::Clink, "clink");
+    #[cfg(feature = "elvish")]
+    check_shell_value_test!(test_shell_value_elvish, Shell::Elvish, "elvish");
+    #[cfg(feature = "fig")]
+    check_shell_value_test!(test_shell_value_fig, Shell::Fig, "fig");
+    #[cfg(feature = "fish")]
+    check_shell_value_test!(test_shell_value_fish, Shell::Fish, "fish");
+    #[cfg(feature = "man")]
+    check_shell_value_test!(test_shell_value_man, Shell::Man, "man");
+    #[cfg(feature = "murex")]
+    check_shell_value_test!(test_shell_value_murex, Shell::Murex, "murex");
+    #[cfg(feature = "nushell")]
+    check_shell_value_test!(test_shell_value_nushell, Shell::Nu, "nushell");
+    #[cfg(feature = "powershell")]
+    check_shell_value_test!(test_shell_value_powershell, Shell::PowerShell, "powershell");
+    #[cfg(feature = "tcsh")]
+    check_shell_value_test!(test_shell_value_tcsh, Shell::Tcsh, "tcsh");
+    #[cfg(feature = "xonsh")]
+    check_shell_value_test!(test_shell_value_xonsh, Shell::Xonsh, "xonsh");
+    #[cfg(feature = "zsh")]
+    check_shell_value_test!(test_shell_value_zsh, Shell::Zsh, "zsh");
+
+    #[test]
+    fn check_order() {
+        let names = Shell::value_variants()
+            .iter()
+            .map(|shell| shell.to_possible_value().unwrap().get_name().to_owned())
+            .collect::<Vec<_>>();
+
+        let mut sorted = names.clone();
+        sorted.sort_unstable();
+
+        assert_eq!(names, sorted);
+
+        let correct_order = [
+            ("bash", cfg!(feature = "bash")),
+            ("carapace", cfg!(feature = "carapace")),
+            ("clink", cfg!(feature = "clink")),
+            ("elvish", cfg!(feature = "elvish")),
+            ("fig", cfg!(feature = "fig")),
+            ("fish", cfg!(feature = "fish")),
+            ("man", cfg!(feature = "man")),
+            ("murex", cfg!(feature = "murex")),
+            ("nushell", cfg!(feature = "nushell")),
+            ("powershell", cfg!(feature = "powershell")),
+            ("tcsh", cfg!(feature = "tcsh")),
+            ("xonsh", cfg!(feature = "xonsh")),
+            ("zsh", cfg!(feature = "zsh")),
+        ]
+        .iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(shell, _)| *shell)
+        .collect::<Vec<_>>();
+
+        assert_eq!(names, correct_order);
+    }
+
+    #[test]
+    fn variants_for_build_matches_value_variants() {
+        let variants = Shell::variants_for_build()
+            .iter()
+            .map(|shell| shell.to_possible_value().unwrap().get_name().to_owned())
+            .collect::<Vec<_>>();
+        let value_variants = Shell::value_variants()
+            .iter()
+            .map(|shell| shell.to_possible_value().unwrap().get_name().to_owned())
+            .collect::<Vec<_>>();
+
+        assert_eq!(variants, value_variants);
+    }
+
+    #[cfg(all(feature = "fish", feature = "zsh"))]
+    #[test]
+    fn setup_doc() {
+        assert_eq!(
+            Shell::Fish.setup_doc("app"),
+            "```fish
+mkdir -p ~/.config/fish/completions
+app completions fish > ~/.config/fish/completions/app.fish
+```
+",
+        );
+
+        let zsh = Shell::Zsh.setup_doc("app");
+        assert!(zsh.contains("app completions zsh > ~/.zfunc/_app\n"));
+
+        for shell in Shell::value_variants() {
+            let name = shell.to_possible_value().unwrap().get_name().to_owned();
+            assert!(
+                shell
+                    .setup_doc("app")
+                    .contains(&format!("app completions {name}")),
+                "{shell:?}",
+            );
+        }
+    }
+
+    #[cfg(feature = "elvish")]
+    #[test]
+    fn elvish_registers_arg_completer() {
+        let mut command = clap::Command::new("app")
+            .bin_name("my-app")
+            .subcommand(clap::Command::new("sub"));
+        let script = generate(Shell::Elvish, &mut command);
+
+        assert!(script.contains("set edit:completion:arg-completer[my-app] = "));
+        assert!(script.contains("var command = 'my-app'"));
+    }
+
+    // `clap::Command` owns its subcommands so a chain can repeat names but never form a cycle,
+    // every backend's recursion is bounded by the depth of the tree
+    #[cfg(feature = "bash")]
+    #[test]
+    fn repeated_subcommand_chain_terminates() {
+        fn chain() -> clap::Command {
+            ["a", "b", "a", "b"]
+                .iter()
+                .rev()
+                .fold(None, |child, name| {
+                    let command = clap::Command::new(*name);
+                    Some(match child {
+                        Some(child) => command.subcommand(child),
+                        None => command,
+                    })
+                })
+                .unwrap()
+        }
+
+        for shell in Shell::value_variants() {
+            let mut command = clap::Command::new("app").subcommand(chain());
+            let script = generate(*shell, &mut command);
+
+            assert!(script.contains("app"), "{shell:?}");
+        }
+
+        let mut command = clap::Command::new("app").subcommand(chain());
+        let script = generate(Shell::Bash, &mut command);
+
+        assert!(script.contains("app__subcmd__a__subcmd__b__subcmd__a__subcmd__b)"));
+    }
+
+    #[cfg(all(feature = "fish", feature = "zsh"))]
+    #[test]
+    fn command_value_hints() {
+        fn command() -> clap::Command {
+            clap::Command::new("app")
+                .arg(
+                    clap::Arg::new("program")
+                        .long("program")
+                        .value_hint(clap::ValueHint::CommandName),
+                )
+                .arg(
+                    clap::Arg::new("script")
+                        .long("script")
+                        .value_hint(clap::ValueHint::CommandString),
+                )
+        }
+
+        let zsh = generate(Shell::Zsh, &mut command());
+        assert!(zsh.contains("'--program=[]: :_command_names -e'"));
+        assert!(zsh.contains("'--script=[]: :_cmdstring'"));
+
+        let fish = generate(Shell::Fish, &mut command());
+        assert!(fish.contains("-l program -r -f -a \"(__fish_complete_command)\""));
+        assert!(fish.contains("-l script -r -f -a \"(__fish_complete_command)\""));
+    }
+
+    #[cfg(feature = "bash")]
+    #[test]
+    fn bash_registers_for_inline_and_file_use() {
+        let mut command = clap::Command::new("app");
+        let script = generate(Shell::Bash, &mut command);
+
+        assert!(script.contains("complete -F _app -o nosort -o bashdefault -o default app\n"));
+        assert_eq!(
+            clap_complete::Generator::file_name(&Shell::Bash, "app"),
+            "app.bash"
+        );
+    }
+
+    #[cfg(feature = "fish")]
+    #[test]
+    fn fish_optional_value() {
+        let mut command = clap::Command::new("app")
+            .arg(
+                clap::Arg::new("color")
+                    .long("color")
+                    .help("When to use color")
+                    .num_args(0..=1)
+                    .default_missing_value("always")
+                    .value_parser(["always", "never"]),
+            )
+            .arg(clap::Arg::new("output").long("output").help("Output file"));
+        let script = generate(Shell::Fish, &mut command);
+
+        assert!(script
+            .contains("complete -c app -l color -d 'When to use color' -f -a \"always\\t''\n"));
+        assert!(script.contains("complete -c app -l output -d 'Output file' -r\n"));
+    }
+
+    #[cfg(feature = "bash")]
+    #[test]
+    fn try_generate_to_string() {
+        let mut command = clap::Command::new("app");
+        let script = Shell::Bash.try_generate_to_string(&mut command).unwrap();
+
+        assert!(script.contains("_app()"));
+    }
+
+    #[test]
+    fn try_generate_to_string_invalid_utf8() {
+        struct InvalidUtf8;
+
+        impl clap_complete::Generator for InvalidUtf8 {
+            fn file_name(&self, name: &str) -> String {
+                name.to_owned()
+            }
+
+            fn generate(&self, _cmd: &clap::Command, buf: &mut dyn std::io::Write) {
+                buf.write_all(b"complete \xff").unwrap();
+            }
+        }
+
+        let mut command = clap::Command::new("app");
+        let error = generate_to_string(InvalidUtf8, &mut command).unwrap_err();
+
+        assert_eq!(error.as_bytes(), b"complete \xff");
+        assert_eq!(error.utf8_error().valid_up_to(), "complete ".len());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn generate_all_to_parallel_matches_sequential() {
+        let command = clap::Command::new("app")
+            .arg(clap::Arg::new("verbose").long("verbose"))
+            .subcommand(clap::Command::new("sub"));
+        let parallel_dir = tempfile::tempdir().unwrap();
+        let sequential_dir = tempfile::tempdir().unwrap();
+
+        let paths = Shell::generate_all_to_parallel(&command, parallel_dir.path()).unwrap();
+
+        assert_eq!(paths.len(), Shell::variants_for_build().len());
+        for (shell, path) in Shell::variants_for_build().iter().zip(paths) {
+            let sequential = shell
+                .generate_to(&mut command.clone(), sequential_dir.path())
+                .unwrap();
+            assert_eq!(path.file_name(), sequential.file_name());
+            assert_eq!(
+                std::fs::read(&path).unwrap(),
+                std::fs::read(&sequential).unwrap(),
+                "{shell:?}",
+            );
+        }
+    }
+
+    #[cfg(feature = "bash")]
+    #[test]
+    fn display_order() {
+        let mut command = clap::Command::new("app")
+            .arg(clap::Arg::new("first").long("first").display_order(2))
+            .arg(clap::Arg::new("second").long("second").display_order(1))
+            .subcommand(clap::Command::new("first").display_order(2))
+            .subcommand(clap::Command::new("second").display_order(1));
+
+        let script = generate(Shell::Bash, &mut command.clone());
+        assert!(script.contains("opts=\"-h --first --second --help first second help\""));
+
+        let mut buffer = Vec::new();
+        Shell::Bash.generate_with_options(
+            &mut command,
+            GenerateOptions::new().display_order(true),
+            &mut buffer,
+        );
+        let script = String::from_utf8(buffer).unwrap();
+        assert!(script.contains("opts=\"-h --second --first --help second first help\""));
+
+        // Tied orders are sorted by name, like in `--help`
+        let mut command = clap::Command::new("app")
+            .next_display_order(None)
+            .disable_help_flag(true)
+            .disable_help_subcommand(true)
+            .arg(clap::Arg::new("zebra").long("zebra"))
+            .arg(clap::Arg::new("apple").long("apple"))
+            .arg(clap::Arg::new("mango").short('m'))
+            .subcommand(clap::Command::new("zulu"))
+            .subcommand(clap::Command::new("alpha"));
+        let help = command.render_help().to_string();
+        let position = |name: &str| help.find(name).unwrap();
+        assert!(position("--apple") < position("-m") && position("-m") < position("--zebra"));
+        assert!(position("alpha") < position("zulu"));
+
+        let mut buffer = Vec::new();
+        Shell::Bash.generate_with_options(
+            &mut command,
+            GenerateOptions::new().display_order(true),
+            &mut buffer,
+        );
+        let script = String::from_utf8(buffer).unwrap();
+        assert!(
+            script.contains("opts=\"-m --apple --zebra alpha zulu\""),
+            "{script}"
+        );
+    }
+
+    #[test]
+    fn include_hidden() {
+        fn command() -> clap::Command {
+            clap::Command::new("app")
+                .arg(clap::Arg::new("classified").long("classified").hide(true))
+                .arg(clap::Arg::new("color").long("color").alias("colour"))
+                .subcommand(
+                    clap::Command::new("secret")
+                        .hide(true)
+                        .visible_alias("sekrit"),
+                )
+        }
+        fn generate_hidden(shell: Shell, include_hidden: bool) -> String {
+            let mut buffer = Vec::new();
+            shell.generate_with_options(
+                &mut command(),
+                GenerateOptions::new().include_hidden(include_hidden),
+                &mut buffer,
+            );
+            String::from_utf8(buffer).unwrap()
+        }
+
+        for shell in Shell::value_variants() {
+            let script = generate_hidden(*shell, false);
+            assert_eq!(script, generate(*shell, &mut command()), "{shell:?}");
+            for hidden in ["classified", "colour", "secret", "sekrit"] {
+                assert!(!script.contains(hidden), "{shell:?} completes {hidden}");
+            }
+
+            let script = generate_hidden(*shell, true);
+            assert!(script.contains("classified"), "{shell:?}");
+            assert!(script.contains("secret"), "{shell:?}");
+            if shell.includes_aliases() {
+                assert!(script.contains("colour"), "{shell:?}");
+                assert!(script.contains("sekrit"), "{shell:?}");
+            }
+        }
+
+        let mut command = clap::Command::new("app")
+            .arg(clap::Arg::new("classified").long("classified").hide(true))
+            .subcommand(clap::Command::new("secret").hide(true));
+        Shell::all()[0].generate_with_options(
+            &mut command,
+            GenerateOptions::new().include_hidden(true),
+            &mut Vec::new(),
+        );
+        assert!(command
+            .get_arguments()
+            .all(|arg| arg.get_id() != "classified" || arg.is_hide_set()));
+        assert!(command.find_subcommand("secret").unwrap().is_hide_set());
+    }
+
+    #[cfg(feature = "nushell")]
+    #[test]
+    fn nu_forms() {
+        fn generate_nu(nu_form: NuForm) -> String {
+            let mut command = clap::Command::new("app")
+                .disable_help_flag(true)
+                .disable_help_subcommand(true)
+                .arg(
+                    clap::Arg::new("verbose")
+                        .long("verbose")
+                        .help("Be loud")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .subcommand(clap::Command::new("sub").about("A sub"));
+            let mut buffer = Vec::new();
+            Shell::Nu.generate_with_options(
+                &mut command,
+                GenerateOptions::new().nu_form(nu_form),
+                &mut buffer,
+            );
+            String::from_utf8(buffer).unwrap()
+        }
+
+        assert_eq!(
+            generate_nu(NuForm::Source),
+            "module completions {
+
+  export extern app [
+    --verbose                 # Be loud
+  ]
+
+  # A sub
+  export extern \"app sub\" [
+  ]
+
+}
+
+export use completions *
+",
+        );
+        assert_eq!(
+            generate_nu(NuForm::Overlay),
+            "export extern app [
+  --verbose                 # Be loud
+]
+
+# A sub
+export extern \"app sub\" [
+]
+",
+        );
+    }
+
+    #[cfg(feature = "zsh")]
+    #[test]
+    fn flatten_help_keeps_structure() {
+        fn command(flatten_help: bool) -> clap::Command {
+            clap::Command::new("app")
+                .flatten_help(flatten_help)
+                .subcommand(
+                    clap::Command::new("remote")
+                        .subcommand(clap::Command::new("add").arg(clap::Arg::new("name"))),
+                )
+        }
+
+        for shell in Shell::value_variants() {
+            assert_eq!(
+                generate(*shell, &mut command(true)),
+                generate(*shell, &mut command(false)),
+                "{shell:?}",
+            );
+        }
+
+        let script = generate(Shell::Zsh, &mut command(true));
+        assert!(script.contains("\":: :_app__subcmd__remote_commands\" \\\n"));
+        assert!(script.contains("(add)\n_arguments \"${_arguments_options[@]}\" : \\\n"));
+    }
+
+    #[cfg(feature = "carapace")]
+    #[test]
+    fn carapace_global_args_are_persistent() {
+        let mut command = clap::Command::new("app")
+            .disable_help_flag(true)
+            .disable_help_subcommand(true)
+            .arg(
+                clap::Arg::new("verbose")
+                    .long("verbose")
+                    .short('v')
+                    .global(true)
+                    .action(clap::ArgAction::SetTrue)
+                    .help("Be loud"),
+            )
+            .subcommand(
+                clap::Command::new("sub")
+                    .about("A sub")
+                    .arg(clap::Arg::new("local").long("local").help("Local")),
+            );
+
+        assert_eq!(
+            generate(Shell::Carapace, &mut command),
+            "# yaml-language-server: $schema=https://carapace.sh/schemas/command.json
+name: app
+description: ''
+persistentflags:
+  -v, --verbose: Be loud
+commands:
+- name: sub
+  description: A sub
+  flags:
+    --local=: Local
+",
+        );
+    }
+
+    #[cfg(all(feature = "bash", feature = "zsh"))]
+    #[test]
+    fn version_header() {
+        fn generate_header(shell: Shell, version_header: bool) -> String {
+            let mut command = clap::Command::new("app").version("1.2.3");
+            let mut buffer = Vec::new();
+            shell.generate_with_options(
+                &mut command,
+                GenerateOptions::new().version_header(version_header),
+                &mut buffer,
+            );
+            String::from_utf8(buffer).unwrap()
+        }
+
+        assert!(!generate_header(Shell::Bash, false).contains("generated by"));
+        assert!(
+            generate_header(Shell::Bash, true).starts_with("# generated by app 1.2.3\n_app() {")
+        );
+        assert!(generate_header(Shell::Zsh, true)
+            .starts_with("#compdef app\n# generated by app 1.2.3\n\nautoload"));
+        #[cfg(feature = "fig")]
+        assert!(generate_header(Shell::Fig, true).starts_with("// generated by app 1.2.3\n"));
+        #[cfg(feature = "carapace")]
+        assert!(generate_header(Shell::Carapace, true).starts_with(
+            "# yaml-language-server: $schema=https://carapace.sh/schemas/command.json\n# generated by app 1.2.3\n",
+        ));
+    }
+
+    #[cfg(feature = "zsh")]
+    #[test]
+    fn value_names() {
+        let mut command = clap::Command::new("app").disable_help_flag(true).arg(
+            clap::Arg::new("mount")
+                .long("mount")
+                .value_name("SRC,DST")
+                .action(clap::ArgAction::Append)
+                .help("Bind mount"),
+        );
+        let mut buffer = Vec::new();
+        Shell::Zsh.generate_with_options(
+            &mut command,
+            GenerateOptions::new().value_names(true),
+            &mut buffer,
+        );
+        let script = String::from_utf8(buffer).unwrap();
+
+        assert!(script.contains(
+            "_arguments \"${_arguments_options[@]}\" : \\
+'*--mount=[<SRC,DST> Bind mount]:SRC,DST:_default' \\
+&& ret=0
+"
+        ));
+    }
+
+    #[cfg(feature = "bash")]
+    #[test]
+    fn generate_to_empty_bin_name() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let mut command = clap::Command::new("");
+        let error = Shell::Bash
+            .generate_to(&mut command, out_dir.path())
+            .unwrap_err();
+
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        assert_eq!(std::fs::read_dir(out_dir.path()).unwrap().count(), 0);
+    }
+
+    #[cfg(feature = "bash")]
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic = "the command has neither a bin name nor a name"]
+    fn generate_empty_bin_name() {
+        generate(Shell::Bash, &mut clap::Command::new(""));
+    }
+
+    #[cfg(feature = "fish")]
+    #[test]
+    fn generate_to_with_file_name() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let mut command = clap::Command::new("app");
+
+        let path = Shell::Fish
+            .generate_to_with(
+                &mut command,
+                out_dir.path(),
+                Some("pkg-app.fish".to_owned()),
+            )
+            .unwrap();
+        let default_path = Shell::Fish
+            .generate_to_with(&mut command, out_dir.path(), None)
+            .unwrap();
+
+        assert_eq!(path, out_dir.path().join("pkg-app.fish"));
+        assert_eq!(default_path, out_dir.path().join("app.fish"));
+        assert_eq!(
+            std::fs::read(path).unwrap(),
+            std::fs::read(default_path).unwrap(),
+        );
+    }
+
+    #[cfg(feature = "zsh")]
+    #[test]
+    fn local_arg_shadows_global_arg() {
+        let mut command = clap::Command::new("app")
+            .disable_help_flag(true)
+            .arg(
+                clap::Arg::new("verbose")
+                    .long("verbose")
+                    .global(true)
+                    .action(clap::ArgAction::SetTrue)
+                    .help("Global verbose"),
+            )
+            .subcommand(
+                clap::Command::new("sub").arg(
+                    clap::Arg::new("verbose")
+                        .long("verbose")
+                        .action(clap::ArgAction::SetTrue)
+                        .help("Local verbose"),
+                ),
+            );
+        let script = generate(Shell::Zsh, &mut command);
+        let (root, sub) = script.split_once("(sub)\n").unwrap();
+
+        assert_eq!(root.matches("'--verbose[").count(), 1);
+        assert!(root.contains("'--verbose[Global verbose]' \\\n"));
+        let sub = sub.split_once(";;").unwrap().0;
+        assert_eq!(sub.matches("'--verbose[").count(), 1);
+        assert!(sub.contains("'--verbose[Local verbose]' \\\n"));
+    }
+
+    #[cfg(feature = "fish")]
+    #[test]
+    fn fish_help_and_version() {
+        let mut command = clap::Command::new("app")
+            .version("1.0.0")
+            .propagate_version(true)
+            .subcommand(clap::Command::new("sub").arg(clap::Arg::new("name").long("name")));
+        let mut buffer = Vec::new();
+        Shell::Fish.generate_with_options(
+            &mut command,
+            GenerateOptions::new().fish_help_and_version(false),
+            &mut buffer,
+        );
+        let script = String::from_utf8(buffer).unwrap();
+
+        assert!(generate(Shell::Fish, &mut command.clone())
+            .contains("complete -c app -n \"__fish_app_using_subcommand sub\" -s h -l help"));
+        assert!(!script.contains("-l help"));
+        assert!(!script.contains("-l version"));
+        assert!(
+            script.contains("complete -c app -n \"__fish_app_using_subcommand sub\" -l name -r\n")
+        );
+        assert!(script.contains("string join \\n h/help V/version\n"));
+    }
+
+    #[cfg(feature = "bash")]
+    #[test]
+    fn generate_to_with_progress_reports_path() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let mut command = clap::Command::new("app");
+        let mut written = Vec::new();
+
+        let path = Shell::Bash
+            .generate_to_with_progress(&mut command, out_dir.path(), |path| {
+                written.push(path.to_owned());
+            })
+            .unwrap();
+
+        assert_eq!(written, std::slice::from_ref(&path));
+        assert!(path.is_file());
+    }
+
+    #[cfg(feature = "bash")]
+    #[cfg(feature = "dynamic")]
+    #[test]
+    fn dynamic_completions_command() {
+        let command = clap::Command::new("app").subcommand(dynamic::completions_command());
+        let completions = |args: &[&str]| {
+            let matches = command.clone().get_matches_from(args);
+            let (_, sub_matches) = matches.subcommand().unwrap();
+            let mut buffer = Vec::new();
+            dynamic::generate(sub_matches, &mut command.clone(), &mut buffer)
+                .map(|()| String::from_utf8(buffer).unwrap())
+        };
+
+        assert_eq!(
+            completions(&["app", "completions", "bash"]).unwrap(),
+            generate(Shell::Bash, &mut command.clone()),
+        );
+
+        let registration = completions(&["app", "completions", "bash", "--dynamic"]).unwrap();
+        assert!(registration.contains("COMPLETE=\"bash\""));
+        assert!(registration.contains("_clap_complete_app app"));
+
+        #[cfg(feature = "nushell")]
+        assert_eq!(
+            completions(&["app", "completions", "nushell", "--dynamic"])
+                .unwrap_err()
+                .kind(),
+            std::io::ErrorKind::Unsupported,
+        );
+    }
+
+    #[cfg(feature = "zsh")]
+    #[test]
+    fn zsh_exclusive_arg() {
+        let mut command = clap::Command::new("app")
+            .arg(
+                clap::Arg::new("list")
+                    .long("list")
+                    .action(clap::ArgAction::SetTrue)
+                    .exclusive(true),
+            )
+            .arg(clap::Arg::new("name").long("name"))
+            .subcommand(
+                clap::Command::new("sub").arg(
+                    clap::Arg::new("all")
+                        .long("all")
+                        .action(clap::ArgAction::SetTrue)
+                        .exclusive(true),
+                ),
+            );
+
+        let script = generate(Shell::Zsh, &mut command);
+
+        assert!(script.contains("'(--name -h --help)--list[]' \\\n"));
+        assert!(script.contains("'(--list)--name=[]: :_default' \\\n"));
+        assert!(script.contains("'(--list)--help[Print help]' \\\n"));
+        assert!(script.contains("'(-h --help)--all[]' \\\n"));
+    }
+
+    #[cfg(feature = "fish")]
+    #[test]
+    fn generate_rust_literal() {
+        /// Undoes the escapes `str`'s `Debug` output uses
+        fn unescape(literal: &str) -> String {
+            let mut unescaped = String::new();
+            let mut chars = literal.chars();
+            while let Some(character) = chars.next() {
+                if character != '\\' {
+                    unescaped.push(character);
+                    continue;
+                }
+                match chars.next().unwrap() {
+                    'n' => unescaped.push('\n'),
+                    'r' => unescaped.push('\r'),
+                    't' => unescaped.push('\t'),
+                    '0' => unescaped.push('\0'),
+                    'u' => {
+                        let code = chars
+                            .by_ref()
+                            .skip(1)
+                            .take_while(|character| *character != '}')
+                            .collect::<String>();
+                        let code = u32::from_str_radix(&code, 16).unwrap();
+                        unescaped.push(char::from_u32(code).unwrap());
+                    }
+                    escaped => unescaped.push(escaped),
+                }
+            }
+            unescaped
+        }
+
+        let mut command = clap::Command::new("app").arg(
+            clap::Arg::new("quote")
+                .long("quote")
+                .help("Wrap in \"quotes\" or \\backslashes\\ \u{1b}"),
+        );
+        let script = generate(Shell::Fish, &mut command.clone());
+
+        let literal = Shell::Fish.generate_rust_literal("APP_FISH", &mut command);
+
+        let literal = literal
+            .strip_prefix("pub const APP_FISH: &str = \"")
+            .unwrap()
+            .strip_suffix("\";\n")
+            .unwrap();
+        assert!(!literal.contains('\n'));
+        assert!(literal
+            .split("\\\\")
+            .all(|part| !part.replace("\\\"", "").contains('"')));
+        assert_eq!(unescape(literal), script);
+    }
+
+    #[cfg(feature = "bash")]
+    #[test]
+    fn propagate_version() {
+        let mut command = clap::Command::new("app")
+            .version("1.0.0")
+            .propagate_version(true)
+            .subcommand(clap::Command::new("sub").subcommand(clap::Command::new("nested")));
+
+        let script = generate(Shell::Bash, &mut command);
+
+        for subcommand in ["app__subcmd__sub)", "app__subcmd__sub__subcmd__nested)"] {
+            let (_, section) = script.split_once(subcommand).unwrap();
+            let opts = section.lines().nth(1).unwrap();
+            assert!(opts.contains(" --version"), "{subcommand} {opts}");
+        }
+    }
+
+    #[cfg(feature = "zsh")]
+    #[test]
+    fn zsh_forms() {
+        fn generate_zsh(zsh_form: ZshForm) -> String {
+            let mut command = clap::Command::new("app");
+            let mut buffer = Vec::new();
+            Shell::Zsh.generate_with_options(
+                &mut command,
+                GenerateOptions::new().zsh_form(zsh_form),
+                &mut buffer,
+            );
+            String::from_utf8(buffer).unwrap()
+        }
+
+        let autoload = generate_zsh(ZshForm::Autoload);
+        assert_eq!(
+            autoload,
+            generate(Shell::Zsh, &mut clap::Command::new("app"))
+        );
+        assert!(autoload.starts_with("#compdef app\n"));
+        assert!(autoload.ends_with(
+            "if [ \"$funcstack[1]\" = \"_app\" ]; then
+    _app \"$@\"
+else
+    compdef _app app
+fi
+"
+        ));
+
+        let inline = generate_zsh(ZshForm::Inline);
+        assert!(inline.starts_with("\nautoload -U is-at-least\n"));
+        assert!(inline.ends_with("}\n\ncompdef _app app\n"));
+        assert!(!inline.contains("funcstack"));
+    }
+
+    #[cfg(feature = "zsh")]
+    #[test]
+    fn generate_with_value_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let hosts = dir.path().join("hosts");
+        std::fs::write(&hosts, "alpha.example\n\nbeta.example\n").unwrap();
+        let mut command = clap::Command::new("app")
+            .subcommand(clap::Command::new("ssh").arg(clap::Arg::new("host").long("host")));
+
+        let mut buffer = Vec::new();
+        Shell::Zsh
+            .generate_with_value_files(
+                &mut command,
+                HashMap::from([("host".to_owned(), hosts.clone())]),
+                &mut buffer,
+            )
+            .unwrap();
+        let script = String::from_utf8(buffer).unwrap();
+
+        assert!(script.contains("'--host=[]: :(alpha.example beta.example)' \\\n"));
+        assert!(!generate(Shell::Zsh, &mut command.clone()).contains("alpha.example"));
+
+        let error = Shell::Zsh
+            .generate_with_value_files(
+                &mut command,
+                HashMap::from([("port".to_owned(), hosts)]),
+                &mut Vec::new(),
+            )
+            .unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[cfg(feature = "zsh")]
+    #[test]
+    fn zsh_allow_missing_positional() {
+        let mut command = clap::Command::new("app")
+            .disable_help_flag(true)
+            .allow_missing_positional(true)
+            .arg(clap::Arg::new("first").default_value("a"))
+            .arg(
+                clap::Arg::new("second")
+                    .required(true)
+                    .value_parser(["x", "y"]),
+            );
+
+        let script = generate(Shell::Zsh, &mut command);
+
+        // The first positional is optional, the second still completes its values after it
+        assert!(script.contains(
+            "_arguments \"${_arguments_options[@]}\" : \\
+'::first:_default' \\
+':second:(x y)' \\
+&& ret=0
+"
+        ));
+    }
+
+    #[cfg(feature = "bash")]
+    #[test]
+    fn startup_cost_estimate() {
+        assert_eq!(startup_cost::classify(0, 0), StartupCost::Small);
+        assert_eq!(startup_cost::classify(16 * 1024, 100), StartupCost::Small);
+        assert_eq!(
+            startup_cost::classify(16 * 1024 + 1, 0),
+            StartupCost::Medium
+        );
+        assert_eq!(startup_cost::classify(0, 101), StartupCost::Medium);
+        assert_eq!(
+            startup_cost::classify(128 * 1024, 1000),
+            StartupCost::Medium
+        );
+        assert_eq!(
+            startup_cost::classify(128 * 1024 + 1, 0),
+            StartupCost::Large
+        );
+        assert_eq!(startup_cost::classify(0, 1001), StartupCost::Large);
+
+        let small = clap::Command::new("app").arg(clap::Arg::new("verbose").long("verbose"));
+        assert_eq!(
+            Shell::Bash.startup_cost_estimate(&small),
+            StartupCost::Small
+        );
+
+        let large = (0..1000).fold(clap::Command::new("app"), |command, index| {
+            command.subcommand(clap::Command::new(super::leak(&format!("sub{index}"))))
+        });
+        assert_eq!(
+            Shell::Bash.startup_cost_estimate(&large),
+            StartupCost::Large
+        );
+    }
+
+    #[test]
+    fn ignore_errors_does_not_affect_completions() {
+        let command = |ignore_errors| {
+            clap::Command::new("app")
+                .ignore_errors(ignore_errors)
+                .arg(clap::Arg::new("name").long("name").required(true))
+                .arg(clap::Arg::new("file"))
+                .subcommand(clap::Command::new("sub").ignore_errors(ignore_errors))
+        };
+
+        for shell in Shell::variants_for_build() {
+            assert_eq!(
+                generate(*shell, &mut command(true)),
+                generate(*shell, &mut command(false)),
+                "{shell:?}",
+            );
+        }
+    }
+
+    #[cfg(feature = "fig")]
+    #[test]
+    fn fig_icon_and_long_about() {
+        fn generate_fig(options: GenerateOptions) -> String {
+            let mut command = clap::Command::new("app")
+                .about("The app")
+                .long_about("The app, at length")
+                .disable_help_flag(true)
+                .disable_help_subcommand(true)
+                .subcommand(
+                    clap::Command::new("sub")
+                        .about("A sub")
+                        .subcommand(clap::Command::new("nested").about("Nested")),
+                );
+            let mut buffer = Vec::new();
+            Shell::Fig.generate_with_options(&mut command, options, &mut buffer);
+            String::from_utf8(buffer).unwrap()
+        }
+
+        let default = generate_fig(GenerateOptions::new());
+        assert!(default.contains("  description: \"The app\",\n"));
+        assert!(!default.contains("icon"));
+
+        assert_eq!(
+            generate_fig(GenerateOptions::new().fig_icon("🚀").fig_long_about(true)),
+            "const completion: Fig.Spec = {
+  name: \"app\",
+  icon: \"🚀\",
+  description: \"The app, at length\",
+  subcommands: [
+    {
+      name: \"sub\",
+      icon: \"🚀\",
+      description: \"A sub\",
+      subcommands: [
+        {
+          name: \"nested\",
+          icon: \"🚀\",
+          description: \"Nested\",
+        },
+      ],
+    },
+  ],
+};
+
+export default completion;
+",
+        );
+    }
+
+    #[cfg(feature = "zsh")]
+    #[test]
+    fn zsh_require_equals() {
+        let mut command = clap::Command::new("app")
+            .disable_help_flag(true)
+            .arg(
+                clap::Arg::new("color")
+                    .long("color")
+                    .short('c')
+                    .require_equals(true)
+                    .value_parser(["always", "never"]),
+            )
+            .arg(
+                clap::Arg::new("define")
+                    .long("define")
+                    .require_equals(true)
+                    .action(clap::ArgAction::Append)
+                    .conflicts_with("name"),
+            )
+            .arg(clap::Arg::new("name").long("name"));
+
+        let script = generate(Shell::Zsh, &mut command);
+
+        assert!(script.contains(
+            "_arguments \"${_arguments_options[@]}\" : \\
+'-c=-[]: :(always never)' \\
+'--color=-[]: :(always never)' \\
+'(--name)*--define=-[]: :_default' \\
+'--name=[]: :_default' \\
+&& ret=0
+"
+        ));
+    }
+
+    #[cfg(feature = "bash")]
+    #[test]
+    fn run() {
+        let command = clap::Command::new("app")
+            .subcommand(completions_subcommand())
+            .subcommand(clap::Command::new("other"));
+        let run = |args: &[&str]| {
+            let matches = command.clone().get_matches_from(args);
+            let mut buffer = Vec::new();
+            let handled = super::run(&matches, &mut command.clone(), &mut buffer).unwrap();
+            (handled, String::from_utf8(buffer).unwrap())
+        };
+
+        assert_eq!(
+            run(&["app", "completions", "bash"]),
+            (true, generate(Shell::Bash, &mut command.clone())),
+        );
+        assert_eq!(run(&["app", "other"]), (false, String::new()));
+        assert_eq!(run(&["app"]), (false, String::new()));
+    }
+
+    #[test]
+    fn from_env() {
+        fn name(shell: Option<Shell>) -> Option<String> {
+            shell.map(|shell| shell.to_possible_value().unwrap().get_name().to_owned())
+        }
+
+        for (shell, expected) in [
+            ("/bin/bash", cfg!(feature = "bash").then_some("bash")),
+            ("/usr/bin/zsh", cfg!(feature = "zsh").then_some("zsh")),
+            ("/usr/bin/fish", cfg!(feature = "fish").then_some("fish")),
+            ("/bin/ksh", None),
+            // The last four bytes split the `€`
+            ("/bin/€ab", None),
+            ("", None),
+        ] {
+            let detected = with_vars(&[("SHELL", Some(shell))], Shell::from_env);
+            assert_eq!(name(detected).as_deref(), expected, "{shell}");
+        }
+
+        assert_eq!(
+            name(Shell::from_shell_path(Path::new("pwsh.EXE"))).as_deref(),
+            cfg!(feature = "powershell").then_some("powershell")
+        );
+        #[cfg(feature = "nushell")]
+        assert_eq!(
+            name(Shell::from_shell_path(Path::new("/usr/local/bin/nu"))).as_deref(),
+            Some("nushell")
+        );
+    }
+
+    #[cfg(feature = "bash")]
+    #[test]
+    fn generate_to_string_contains_bin_name() {
+        let mut command =
+            clap::Command::new("my-app").arg(clap::Arg::new("verbose").long("verbose"));
+
+        let script = Shell::Bash.generate_to_string(&mut command);
+
+        assert!(script.contains("my-app"));
+        assert_eq!(script, generate(Shell::Bash, &mut command));
+    }
+
+    #[cfg(all(feature = "bash", feature = "zsh"))]
+    #[test]
+    fn display_round_trips() {
+        assert_eq!(Shell::Bash.to_string(), "bash");
+        assert_eq!(format!("{}", Shell::Zsh), "zsh");
+
+        for shell in Shell::variants_for_build() {
+            let parsed = Shell::from_str(&shell.to_string(), false).unwrap();
+            assert_eq!(
+                parsed.to_possible_value().unwrap().get_name(),
+                shell.to_possible_value().unwrap().get_name(),
+            );
+        }
+    }
+
+    #[cfg(feature = "man")]
+    #[test]
+    fn man_page() {
+        let mut command = clap::Command::new("app").about("The app");
+
+        let page = generate(Shell::Man, &mut command);
+
+        assert!(page.starts_with(".ie \\n(.g .ds Aq \\(aq\n"));
+        assert!(page.contains(".TH app 1"));
+        assert!(page.contains("The app"));
+        assert_eq!(
+            clap_complete::Generator::file_name(&Shell::Man, "app"),
+            "app.1"
+        );
+    }
+
+    #[test]
+    fn names() {
+        let possible_values = Shell::value_variants()
+            .iter()
+            .map(|shell| shell.to_possible_value().unwrap().get_name().to_owned())
+            .collect::<Vec<_>>();
+
+        assert_eq!(Shell::names(), possible_values);
+        assert_eq!(Shell::all().len(), possible_values.len());
+    }
+
+    #[test]
+    fn generate_all_to() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let mut command = clap::Command::new("app");
+
+        let paths = Shell::generate_all_to(&mut command, out_dir.path()).unwrap();
+
+        let mut files = std::fs::read_dir(out_dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect::<Vec<_>>();
+        files.sort();
+        let mut sorted_paths = paths.clone();
+        sorted_paths.sort();
+        assert_eq!(paths.len(), Shell::all().len());
+        assert_eq!(files, sorted_paths);
+    }
+
+    #[cfg(feature = "bash")]
+    #[test]
+    fn clap_complete_shell_conversions() {
+        let shell = Shell::try_from(clap_complete::Shell::Bash).unwrap();
+        assert_eq!(shell.to_string(), "bash");
+        assert_eq!(
+            clap_complete::Shell::try_from(shell).unwrap(),
+            clap_complete::Shell::Bash
+        );
+
+        #[cfg(feature = "nushell")]
+        assert_eq!(
+            clap_complete::Shell::try_from(Shell::Nu)
+                .unwrap_err()
+                .to_string(),
+            "nushell"
+        );
+    }
+
+    #[cfg(feature = "fish")]
+    #[test]
+    fn generate_with_bin_name() {
+        let mut command = clap::Command::new("app").bin_name("app");
+        let mut buffer = Vec::new();
+
+        Shell::Fish.generate_with_bin_name(&mut command, "alias-name", &mut buffer);
+
+        let script = String::from_utf8(buffer).unwrap();
+        assert!(script.contains("complete -c alias-name "));
+        assert!(!script.contains("complete -c app "));
+    }
+
+    #[cfg(feature = "fish")]
+    #[test]
+    fn generate_with_bin_name_path() {
+        let mut command = clap::Command::new("app");
+        let mut buffer = Vec::new();
+
+        Shell::Fish.generate_with_bin_name(&mut command, "/usr/local/bin/alias-name", &mut buffer);
+
+        let script = String::from_utf8(buffer).unwrap();
+        assert!(script.contains("complete -c alias-name "));
+        assert!(!script.contains("/usr/local/bin"));
+    }
+
+    #[cfg(feature = "fish")]
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic = "the bin name is empty"]
+    fn generate_with_empty_bin_name() {
+        Shell::Fish.generate_with_bin_name(&mut clap::Command::new("app"), "", &mut Vec::new());
+    }
+
+    #[cfg(feature = "bash")]
+    #[test]
+    fn generate_checked() {
+        /// Fails with `error` once `remaining` bytes have been written
+        struct FailingWriter {
+            remaining: usize,
+            error: std::io::ErrorKind,
+        }
+
+        impl std::io::Write for FailingWriter {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                if self.remaining == 0 {
+                    return Err(self.error.into());
+                }
+                let written = buf.len().min(self.remaining);
+                self.remaining -= written;
+                Ok(written)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut command = clap::Command::new("app");
+
+        let mut writer = FailingWriter {
+            remaining: 16,
+            error: std::io::ErrorKind::StorageFull,
+        };
+        let error = Shell::Bash
+            .generate_checked(&mut command, &mut writer)
+            .unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::StorageFull);
+
+        let mut writer = FailingWriter {
+            remaining: 16,
+            error: std::io::ErrorKind::BrokenPipe,
+        };
+        Shell::Bash
+            .generate_checked(&mut command, &mut writer)
+            .unwrap();
+
+        let mut buffer = Vec::new();
+        Shell::Bash
+            .generate_checked(&mut command, &mut buffer)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            generate(Shell::Bash, &mut command)
+        );
+    }
+
+    #[cfg(feature = "xonsh")]
+    #[test]
+    fn xonsh() {
+        let mut command = clap::Command::new("app")
+            .disable_help_flag(true)
+            .disable_help_subcommand(true)
+            .arg(
+                clap::Arg::new("verbose")
+                    .long("verbose")
+                    .help("Be loud")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .subcommand(
+                clap::Command::new("build")
+                    .about("Build it")
+                    .arg(clap::Arg::new("mode").value_parser(["debug", "release"])),
+            )
+            .subcommand(clap::Command::new("run").about("Run it"));
+
+        assert_eq!(
+            generate(Shell::Xonsh, &mut command),
+            "from xonsh.completers.completer import add_one_completer
+from xonsh.completers.tools import RichCompletion, contextual_command_completer_for
+
+_app_completer_command = {
+    \"description\": \"\",
+    \"options\": {
+        \"--verbose\": (\"Be loud\", False, []),
+    },
+    \"values\": [],
+    \"subcommands\": {
+        \"build\": {
+            \"description\": \"Build it\",
+            \"options\": {},
+            \"values\": [\"debug\", \"release\"],
+            \"subcommands\": {},
+        },
+        \"run\": {
+            \"description\": \"Run it\",
+            \"options\": {},
+            \"values\": [],
+            \"subcommands\": {},
+        },
+    },
+}
+
+
+@contextual_command_completer_for(\"app\")
+def _app_completer(context):
+    node = _app_completer_command
+    words = [arg.value for arg in context.args[1 : context.arg_index]]
+    for word in words:
+        node = node[\"subcommands\"].get(word, node)
+
+    if words and words[-1] in node[\"options\"]:
+        _, takes_value, values = node[\"options\"][words[-1]]
+        if takes_value:
+            if not values:
+                return None
+            return {RichCompletion(value) for value in values if value.startswith(context.prefix)}
+
+    completions = {
+        RichCompletion(name, description=subcommand[\"description\"])
+        for name, subcommand in node[\"subcommands\"].items()
+    }
+    completions |= {
+        RichCompletion(name, description=description)
+        for name, (description, _, _) in node[\"options\"].items()
+    }
+    completions |= {RichCompletion(value) for value in node[\"values\"]}
+    return {
+        completion for completion in completions if completion.startswith(context.prefix)
+    }
+
+
+add_one_completer(\"app\", _app_completer, \"start\")
+",
+        );
+    }
+
+    #[cfg(feature = "tcsh")]
+    #[test]
+    fn tcsh() {
+        let mut command = clap::Command::new("app")
+            .disable_help_flag(true)
+            .arg(
+                clap::Arg::new("verbose")
+                    .long("verbose")
+                    .short('v')
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                clap::Arg::new("color")
+                    .long("color")
+                    .value_parser(["always", "never"]),
+            )
+            .arg(clap::Arg::new("output").long("output").short('o'))
+            .arg(clap::Arg::new("mode").value_parser(["debug", "release"]));
+
+        assert_eq!(
+            generate(Shell::Tcsh, &mut command),
+            "complete app \\
+    'c/--/(verbose color output)/' \\
+    'c/-/(v o)/' \\
+    'n/--color/(always never)/' \\
+    'n/--output/f/' \\
+    'n/-o/f/' \\
+    'p/1/(debug release)/'
+",
+        );
+
+        let mut command = clap::Command::new("app")
+            .disable_help_flag(true)
+            .disable_help_subcommand(true)
+            .subcommand(
+                clap::Command::new("build").visible_alias("b").arg(
+                    clap::Arg::new("release")
+                        .long("release")
+                        .action(clap::ArgAction::SetTrue),
+                ),
+            );
+
+        assert_eq!(
+            generate(Shell::Tcsh, &mut command),
+            "complete app \\
+    'n/build/(--release)/' \\
+    'n/b/(--release)/' \\
+    'p/1/(build b)/'
+",
+        );
+    }
+
+    #[cfg(feature = "bash")]
+    #[test]
+    fn source_hint() {
+        assert_eq!(
+            Shell::Bash.source_hint("app").unwrap(),
+            "eval \"$(app completions bash)\""
+        );
+        #[cfg(feature = "nushell")]
+        {
+            assert_eq!(
+                Shell::Nu.source_hint("app").unwrap(),
+                "source ~/.config/nushell/completions/app.nu"
+            );
+            assert!(Shell::Nu
+                .setup_doc("app")
+                .contains(&Shell::Nu.source_hint("app").unwrap()));
+        }
+        #[cfg(feature = "man")]
+        assert_eq!(Shell::Man.source_hint("app"), None);
+    }
+
+    #[cfg(feature = "zsh")]
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        assert_eq!(serde_json::to_string(&Shell::Zsh).unwrap(), "\"zsh\"");
+        assert_eq!(
+            serde_json::from_str::<Shell>("\"zsh\"").unwrap(),
+            Shell::Zsh
+        );
+
+        let error = serde_json::from_str::<Shell>("\"ksh\"").unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("unknown shell `ksh`, expected one of: bash, "));
+    }
+
+    #[cfg(all(feature = "serde", not(feature = "zsh")))]
+    #[test]
+    fn serde_disabled_shell() {
+        let error = serde_json::from_str::<Shell>("\"zsh\"").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the `zsh` shell needs clap_complete_command's `zsh` feature"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_feature_names() {
+        for shell in Shell::all() {
+            assert!(
+                serialization::FEATURE_GATED
+                    .contains(&(shell.canonical_name(), shell.feature_name().unwrap())),
+                "{shell:?}"
+            );
+        }
+    }
+
+    #[cfg(all(feature = "serde", not(feature = "murex")))]
+    #[test]
+    fn serde_disabled_murex() {
+        let error = serde_json::from_str::<Shell>("\"murex\"").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the `murex` shell needs clap_complete_command's `murex` feature"
+        );
+    }
+
+    #[cfg(feature = "zsh")]
+    #[test]
+    fn comparisons() {
+        let mut shells = std::collections::HashSet::new();
+        shells.insert(Shell::Zsh);
+        shells.insert(Shell::Zsh);
+        assert_eq!(shells.len(), 1);
+
+        let mut sorted = Shell::value_variants().to_vec();
+        sorted.sort_unstable();
+        assert_eq!(sorted, Shell::value_variants());
+    }
+
+    #[cfg(all(feature = "bash", feature = "fish", feature = "powershell"))]
+    #[test]
+    fn install() {
+        let home = tempfile::tempdir().unwrap();
+        let home_path = home.path().to_str().unwrap();
+
+        let mut command = clap::Command::new("app");
+        let (fish, bash, powershell) = with_vars(
+            &[
+                ("HOME", Some(home_path)),
+                ("XDG_CONFIG_HOME", None),
+                ("XDG_DATA_HOME", None),
+            ],
+            || {
+                (
+                    Shell::Fish.install(&mut command),
+                    Shell::Bash.install(&mut command),
+                    Shell::PowerShell.install(&mut command),
+                )
+            },
+        );
+
+        let fish = fish.unwrap();
+        assert_eq!(fish, home.path().join(".config/fish/completions/app.fish"));
+        assert!(fish.is_file());
+        let bash = bash.unwrap();
+        assert_eq!(
+            bash,
+            home.path()
+                .join(".local/share/bash-completion/completions/app")
+        );
+        assert!(std::fs::read_to_string(bash)
+            .unwrap()
+            .contains("complete -F _comp_cmd_app "));
+        #[cfg(not(windows))]
+        assert_eq!(
+            powershell.unwrap(),
+            home.path().join(".config/powershell/_app.ps1")
+        );
+    }
+
+    #[cfg(all(
+        feature = "bash",
+        feature = "elvish",
+        feature = "fish",
+        feature = "powershell",
+        feature = "zsh"
+    ))]
+    #[test]
+    fn completion_dir() {
+        let bash = with_vars(
+            &[
+                ("HOME", Some("/home/user")),
+                ("XDG_DATA_HOME", Some("/data")),
+            ],
+            || Shell::Bash.completion_dir(),
+        );
+        assert_eq!(
+            bash,
+            Some(PathBuf::from("/data/bash-completion/completions"))
+        );
+
+        let bash = with_vars(
+            &[
+                ("HOME", Some("/home/user")),
+                ("XDG_DATA_HOME", Some("relative")),
+            ],
+            || Shell::Bash.completion_dir(),
+        );
+        assert_eq!(
+            bash,
+            Some(PathBuf::from(
+                "/home/user/.local/share/bash-completion/completions"
+            ))
+        );
+
+        let (fish, zsh) = with_vars(
+            &[("HOME", Some("/home/user")), ("XDG_CONFIG_HOME", None)],
+            || (Shell::Fish.completion_dir(), Shell::Zsh.completion_dir()),
+        );
+        assert_eq!(
+            fish,
+            Some(PathBuf::from("/home/user/.config/fish/completions"))
+        );
+        assert_eq!(zsh, Some(PathBuf::from("/home/user/.zfunc")));
+
+        let fish = with_vars(&[("HOME", None), ("USERPROFILE", None)], || {
+            Shell::Fish.completion_dir()
+        });
+        assert_eq!(fish, None);
+
+        assert_eq!(Shell::PowerShell.completion_dir(), None);
+        assert_eq!(Shell::Elvish.completion_dir(), None);
+        #[cfg(feature = "murex")]
+        assert_eq!(Shell::Murex.completion_dir(), None);
+        #[cfg(feature = "tcsh")]
+        {
+            assert_eq!(Shell::Tcsh.completion_dir(), None);
+            assert_eq!(
+                Shell::Tcsh
+                    .install(&mut clap::Command::new("app"))
+                    .unwrap_err()
+                    .kind(),
+                std::io::ErrorKind::Unsupported
+            );
+        }
+    }
+
+    #[cfg(all(feature = "fish", feature = "zsh"))]
+    #[test]
+    fn completions_subcommand_parses_shell() {
+        let command = clap::Command::new("app")
+            .subcommand(completions_subcommand())
+            .subcommand(completions_command_named("complete"));
+        command.clone().debug_assert();
+
+        let matches = command
+            .clone()
+            .get_matches_from(["app", "completions", "zsh"]);
+        let Some(("completions", sub_matches)) = matches.subcommand() else {
+            panic!("expected the `completions` subcommand");
+        };
+        assert_eq!(sub_matches.get_one::<Shell>("shell"), Some(&Shell::Zsh));
+
+        let matches = command
+            .clone()
+            .get_matches_from(["app", "complete", "fish"]);
+        let Some(("complete", sub_matches)) = matches.subcommand() else {
+            panic!("expected the `complete` subcommand");
+        };
+        assert_eq!(sub_matches.get_one::<Shell>("shell"), Some(&Shell::Fish));
+
+        assert!(command
+            .try_get_matches_from(["app", "completions"])
+            .is_err());
+    }
+
+    #[cfg(feature = "elvish")]
+    #[cfg(feature = "derive")]
+    #[test]
+    fn completions_args() {
+        use clap::{CommandFactory, Parser, Subcommand};
+
+        #[derive(Parser)]
+        struct Cli {
+            #[command(subcommand)]
+            command: Commands,
+        }
+
+        #[derive(Subcommand)]
+        enum Commands {
+            Completions(CompletionsArgs),
+        }
+
+        Cli::command().debug_assert();
+        let Commands::Completions(args) = Cli::parse_from(["app", "completions", "elvish"]).command;
+        assert_eq!(args.shell, Shell::Elvish);
+
+        let mut buffer = Vec::new();
+        args.generate(&mut Cli::command(), &mut buffer);
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            generate(Shell::Elvish, &mut Cli::command())
+        );
+    }
+
+    #[cfg(all(feature = "bash", feature = "fish"))]
+    #[test]
+    fn bin_name_path() {
+        let mut command = clap::Command::new("app").bin_name("/usr/local/bin/app");
+        let script = generate(Shell::Fish, &mut command);
+        assert!(script.contains("complete -c app "));
+        assert!(!script.contains("/usr/local/bin"));
+
+        let out_dir = tempfile::tempdir().unwrap();
+        let path = Shell::Bash
+            .generate_to(
+                &mut clap::Command::new("app").bin_name("bin/app"),
+                out_dir.path(),
+            )
+            .unwrap();
+        assert_eq!(path, out_dir.path().join("app.bash"));
+
+        assert_eq!(bin_name(&clap::Command::new("app")), "app");
+    }
+
+    #[cfg(all(feature = "bash", feature = "fish", feature = "zsh"))]
+    #[test]
+    fn file_name() {
+        assert_eq!(Shell::Zsh.file_name("app"), "_app");
+        assert_eq!(Shell::Bash.file_name("app"), "app.bash");
+        assert_eq!(Shell::Fish.file_name("app"), "app.fish");
+    }
+
+    #[cfg(all(feature = "bash", feature = "powershell", feature = "zsh"))]
+    #[test]
+    fn generate_with_header() {
+        let header = "Generated by app 1.2.3 (clap-complete-command)\n\nDo not edit";
+        let with_header = |shell: Shell| {
+            let mut buffer = Vec::new();
+            shell.generate_with_header(&mut clap::Command::new("app"), header, &mut buffer);
+            String::from_utf8(buffer).unwrap()
+        };
+
+        let bash = with_header(Shell::Bash);
+        assert!(bash
+            .starts_with("# Generated by app 1.2.3 (clap-complete-command)\n#\n# Do not edit\n"));
+        assert!(bash.ends_with(&generate(Shell::Bash, &mut clap::Command::new("app"))));
+
+        let powershell = with_header(Shell::PowerShell);
+        assert!(powershell.starts_with("# Generated by app 1.2.3 (clap-complete-command)\n"));
+
+        let zsh = with_header(Shell::Zsh);
+        assert!(zsh.starts_with("#compdef app\n# Generated by app 1.2.3"));
+    }
+
+    #[test]
+    fn is_available() {
+        assert_eq!(Shell::is_available("bash"), cfg!(feature = "bash"));
+        assert_eq!(
+            Shell::is_available("powershell"),
+            cfg!(feature = "powershell")
+        );
+        assert_eq!(Shell::is_available("fig"), cfg!(feature = "fig"));
+        assert_eq!(Shell::is_available("nushell"), cfg!(feature = "nushell"));
+        assert!(!Shell::is_available("ksh"));
+        assert!(!Shell::is_available("Bash"));
+    }
+
+    #[cfg(feature = "bash")]
+    #[test]
+    fn generate_to_vec() {
+        let mut command = clap::Command::new("app");
+        let bytes = Shell::Bash.generate_to_vec(&mut command);
+        assert!(!bytes.is_empty());
+        assert_eq!(
+            bytes,
+            Shell::Bash.generate_to_string(&mut command).into_bytes()
+        );
+    }
+
+    #[cfg(feature = "zsh")]
+    #[test]
+    fn generate_to_path() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let path = out_dir.path().join("staging-app-completion");
+        let mut command = clap::Command::new("app");
+
+        Shell::Zsh.generate_to_path(&mut command, &path).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            generate(Shell::Zsh, &mut command)
+        );
+        assert_eq!(std::fs::read_dir(out_dir.path()).unwrap().count(), 1);
+    }
+
+    #[cfg(feature = "carapace")]
+    #[test]
+    fn carapace_file_name() {
+        assert_eq!(Shell::Carapace.file_name("app"), "app.yaml");
+
+        let out_dir = tempfile::tempdir().unwrap();
+        let path = Shell::Carapace
+            .generate_to(&mut clap::Command::new("app"), out_dir.path())
+            .unwrap();
+        assert_eq!(path, out_dir.path().join("app.yaml"));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn generate_json() {
+        let mut command = clap::Command::new("app")
+            .about("An app")
+            .disable_help_flag(true)
+            .disable_help_subcommand(true)
+            .arg(
+                clap::Arg::new("verbose")
+                    .short('v')
+                    .long("verbose")
+                    .help("Print more")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .subcommand(
+                clap::Command::new("remote")
+                    .visible_alias("r")
+                    .disable_help_flag(true)
+                    .subcommand(
+                        clap::Command::new("add")
+                            .disable_help_flag(true)
+                            .arg(
+                                clap::Arg::new("name")
+                                    .required(true)
+                                    .help("The remote's name"),
+                            )
+                            .arg(
+                                clap::Arg::new("config")
+                                    .long("config")
+                                    .value_name("FILE")
+                                    .value_hint(clap::ValueHint::FilePath),
+                            )
+                            .arg(
+                                clap::Arg::new("protocol")
+                                    .long("protocol")
+                                    .value_parser(["ssh", "https"]),
+                            )
+                            .arg(clap::Arg::new("secret").long("secret").hide(true)),
+                    ),
+            );
+
+        let mut buffer = Vec::new();
+        super::generate_json(&mut command, &mut buffer);
+
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            r#"{
+  "about": "An app",
+  "aliases": [],
+  "name": "app",
+  "options": [
+    {
+      "help": "Print more",
+      "id": "verbose",
+      "longs": [
+        "verbose"
+      ],
+      "possible_values": [],
+      "required": false,
+      "shorts": [
+        "v"
+      ],
+      "takes_value": false,
+      "value_hint": null,
+      "value_names": []
+    }
+  ],
+  "positionals": [],
+  "subcommands": [
+    {
+      "about": null,
+      "aliases": [
+        "r"
+      ],
+      "name": "remote",
+      "options": [],
+      "positionals": [],
+      "subcommands": [
+        {
+          "about": null,
+          "aliases": [],
+          "name": "add",
+          "options": [
+            {
+              "help": null,
+              "id": "config",
+              "longs": [
+                "config"
+              ],
+              "possible_values": [],
+              "required": false,
+              "shorts": [],
+              "takes_value": true,
+              "value_hint": "file-path",
+              "value_names": [
+                "FILE"
+              ]
+            },
+            {
+              "help": null,
+              "id": "protocol",
+              "longs": [
+                "protocol"
+              ],
+              "possible_values": [
+                {
+                  "help": null,
+                  "name": "ssh"
+                },
+                {
+                  "help": null,
+                  "name": "https"
+                }
+              ],
+              "required": false,
+              "shorts": [],
+              "takes_value": true,
+              "value_hint": null,
+              "value_names": []
+            }
+          ],
+          "positionals": [
+            {
+              "help": "The remote's name",
+              "id": "name",
+              "possible_values": [],
+              "required": true,
+              "value_hint": null,
+              "value_names": []
+            }
+          ],
+          "subcommands": []
+        }
+      ]
+    }
+  ]
+}
+"#
+        );
+    }
+
+    #[cfg(all(feature = "bash", feature = "fish", feature = "zsh"))]
+    #[test]
+    fn function_prefix() {
+        let generate_prefixed = |shell: Shell| {
+            let mut command = clap::Command::new("my-app").subcommand(clap::Command::new("remote"));
+            let mut buffer = Vec::new();
+            let options = GenerateOptions::new().function_prefix("acme");
+            shell.generate_with_options(&mut command, options, &mut buffer);
+            String::from_utf8(buffer).unwrap()
+        };
+
+        let zsh = generate_prefixed(Shell::Zsh);
+        assert!(zsh.starts_with("#compdef my-app\n"));
+        assert!(zsh.contains("\n_acme_my-app() {\n"));
+        assert!(zsh.contains("\n_acme_my-app_commands() {\n"));
+        assert!(zsh.contains("(( $+functions[_acme_my-app__subcmd__remote_commands] ))"));
+        assert!(zsh.contains("if [ \"$funcstack[1]\" = \"_acme_my-app\" ]; then"));
+        assert!(zsh.contains("compdef _acme_my-app my-app\n"));
+        assert!(!zsh.contains(" _my-app"));
+
+        let bash = generate_prefixed(Shell::Bash);
+        assert!(bash.starts_with("_acme_my__app() {\n"));
+        assert!(bash.contains("complete -F _acme_my__app "));
+        assert!(!bash.contains(" _my__app"));
+
+        assert_eq!(
+            generate_prefixed(Shell::Fish),
+            generate(
+                Shell::Fish,
+                &mut clap::Command::new("my-app").subcommand(clap::Command::new("remote"))
+            )
+        );
+    }
+
+    #[cfg(feature = "all-shells")]
+    #[test]
+    fn all_shells() {
+        assert_eq!(
+            Shell::names(),
+            [
+                "bash",
+                "carapace",
+                "clink",
+                "elvish",
+                "fig",
+                "fish",
+                "man",
+                "murex",
+                "nushell",
+                "powershell",
+                "tcsh",
+                "xonsh",
+                "zsh",
+            ]
+        );
+    }
+
+    #[cfg(feature = "murex")]
+    #[test]
+    fn murex() {
+        let mut command = clap::Command::new("app")
+            .disable_help_flag(true)
+            .disable_help_subcommand(true)
+            .arg(
+                clap::Arg::new("verbose")
+                    .short('v')
+                    .long("verbose")
+                    .help("Print more")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                clap::Arg::new("color")
+                    .long("color")
+                    .help("When to use {colors}")
+                    .value_parser(["always", "never"]),
+            )
+            .subcommand(
+                clap::Command::new("remote")
+                    .about("Manage remotes")
+                    .visible_alias("r")
+                    .disable_help_flag(true)
+                    .arg(
+                        clap::Arg::new("config")
+                            .long("config")
+                            .value_hint(clap::ValueHint::FilePath),
+                    )
+                    .arg(
+                        clap::Arg::new("host")
+                            .long("host")
+                            .value_hint(clap::ValueHint::Hostname),
+                    )
+                    .arg(clap::Arg::new("name")),
+            );
+
+        assert_eq!(
+            generate(Shell::Murex, &mut command),
+            r#"autocomplete set app { [
+    {
+        "FlagsDesc": {
+            "--verbose": "Print more",
+            "-v": "Print more",
+            "--color": "When to use \u007bcolors\u007d",
+            "remote": "Manage remotes",
+            "r": "Manage remotes"
+        },
+        "FlagValues": {
+            "--color": [
+                {
+                    "Flags": [ "always", "never" ]
+                }
+            ],
+            "remote": [
+                {
+                    "FlagsDesc": {
+                        "--config": "",
+                        "--host": ""
+                    },
+                    "FlagValues": {
+                        "--config": [
+                            {
+                                "IncFiles": true
+                            }
+                        ],
+                        "--host": [
+                            {
+                                "AllowAny": true
+                            }
+                        ]
+                    },
+                    "IncFiles": true,
+                    "AllowMultiple": true,
+                    "Optional": true
+                }
+            ],
+            "r": [
+                {
+                    "FlagsDesc": {
+                        "--config": "",
+                        "--host": ""
+                    },
+                    "FlagValues": {
+                        "--config": [
+                            {
+                                "IncFiles": true
+                            }
+                        ],
+                        "--host": [
+                            {
+                                "AllowAny": true
+                            }
+                        ]
+                    },
+                    "IncFiles": true,
+                    "AllowMultiple": true,
+                    "Optional": true
+                }
+            ]
+        },
+        "AllowMultiple": true,
+        "Optional": true
+    }
+] }
+"#
+        );
+        assert_eq!(Shell::Murex.file_name("app"), "app.mx");
+    }
+
+    #[cfg(all(feature = "fish", feature = "powershell", feature = "zsh"))]
+    #[test]
+    fn extension() {
+        assert_eq!(Shell::Fish.extension(), Some("fish"));
+        assert_eq!(Shell::PowerShell.extension(), Some("ps1"));
+        assert_eq!(Shell::Zsh.extension(), None);
+
+        for shell in Shell::all() {
+            let file_name = shell.file_name("app");
+            let extension = Path::new(&file_name)
+                .extension()
+                .map(|extension| extension.to_str().unwrap());
+            assert_eq!(shell.extension(), extension, "{shell}");
+        }
+    }
+
+    #[cfg(all(
+        feature = "bash",
+        feature = "fish",
+        feature = "powershell",
+        feature = "zsh"
+    ))]
+    #[test]
+    fn install_instructions() {
+        let fish = Shell::Fish.install_instructions("app");
+        assert!(fish.contains("~/.config/fish/completions"));
+        assert!(fish.contains("\n    app completions fish > ~/.config/fish/completions/app.fish\n"));
+
+        let zsh = Shell::Zsh.install_instructions("app");
+        assert!(zsh.contains("~/.zfunc"));
+        assert!(zsh.contains("\n    fpath+=~/.zfunc\n"));
+
+        let bash = Shell::Bash.install_instructions("app");
+        assert!(bash.contains("bash-completion/completions"));
+        assert!(bash.contains("~/.bashrc"));
+
+        assert!(Shell::PowerShell
+            .install_instructions("app")
+            .contains("$PROFILE"));
+    }
+
+    #[test]
+    fn generate_all_to_tree() {
+        let base_dir = tempfile::tempdir().unwrap();
+        let mut command = clap::Command::new("app");
+
+        let paths = Shell::generate_all_to_tree(&mut command, base_dir.path()).unwrap();
+
+        let expected = Shell::all()
+            .iter()
+            .map(|shell| {
+                base_dir
+                    .path()
+                    .join(shell.to_string())
+                    .join(shell.file_name("app"))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(paths, expected);
+        assert!(paths.iter().all(|path| path.is_file()));
+        assert_eq!(
+            std::fs::read_dir(base_dir.path()).unwrap().count(),
+            Shell::all().len()
+        );
+    }
+
+    #[cfg(feature = "powershell")]
+    #[cfg(windows)]
+    #[test]
+    fn default() {
+        assert_eq!(Shell::default(), Shell::PowerShell);
+    }
+
+    #[cfg(feature = "bash")]
+    #[cfg(not(windows))]
+    #[test]
+    fn default() {
+        assert_eq!(Shell::default(), Shell::Bash);
+    }
+
+    #[test]
+    fn would_be_empty() {
+        let mut command = clap::Command::new("app").version("1.0.0");
+        assert!(super::would_be_empty(&command));
+        command.build();
+        assert!(super::would_be_empty(&command));
+
+        assert!(!super::would_be_empty(
+            &clap::Command::new("app").arg(clap::Arg::new("file"))
+        ));
+        assert!(!super::would_be_empty(
+            &clap::Command::new("app").subcommand(clap::Command::new("remote"))
+        ));
+        assert!(super::would_be_empty(
+            &clap::Command::new("app").arg(clap::Arg::new("secret").long("secret").hide(true))
+        ));
+    }
+
+    #[cfg(feature = "bash")]
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn from_process() {
+        assert_eq!(Shell::from_process(std::process::id()), None);
+
+        let Some(bash) = ["/bin/bash", "/usr/bin/bash"]
+            .into_iter()
+            .find(|path| Path::new(path).exists())
+        else {
+            return;
+        };
+        // The `true` keeps bash from replacing itself with `sleep`
+        let mut child = std::process::Command::new(bash)
+            .args(["-c", "sleep 5; true"])
+            .spawn()
+            .unwrap();
+        // Until the child has exec'd bash, it's still a copy of this process
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(4);
+        let shell = loop {
+            let shell = Shell::from_process(child.id());
+            if shell.is_some() || std::time::Instant::now() > deadline {
+                break shell;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        };
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert_eq!(shell, Some(Shell::Bash));
+    }
+
+    #[cfg(feature = "powershell")]
+    #[test]
+    fn custom_backend() {
+        struct Upper;
+
+        impl clap_complete::Generator for Upper {
+            fn file_name(&self, name: &str) -> String {
+                format!("{name}.upper")
+            }
+
+            fn generate(&self, cmd: &clap::Command, buf: &mut dyn std::io::Write) {
+                buf.write_all(cmd.get_bin_name().unwrap().to_uppercase().as_bytes())
+                    .unwrap();
+            }
+        }
+
+        impl CompletionBackend for Upper {
+            fn name(&self) -> &str {
+                "upper"
+            }
+        }
+
+        let mut buffer = Vec::new();
+        generate_with_backend(
+            Upper,
+            &mut clap::Command::new("app").bin_name("/usr/bin/app"),
+            &mut buffer,
+        );
+        assert_eq!(buffer, b"APP");
+
+        assert_eq!(CompletionBackend::name(&Shell::PowerShell), "powershell");
+    }
+
+    #[cfg(feature = "fig")]
+    #[test]
+    fn fig_file_name() {
+        assert_eq!(Shell::Fig.file_name("app"), "app.ts");
+
+        let out_dir = tempfile::tempdir().unwrap();
+        let path = Shell::Fig
+            .generate_to(&mut clap::Command::new("app"), out_dir.path())
+            .unwrap();
+        assert_eq!(path, out_dir.path().join("app.ts"));
+    }
+
+    #[test]
+    fn generate_buffered() {
+        let mut command = clap::Command::new("app")
+            .arg(clap::Arg::new("verbose").long("verbose"))
+            .subcommand(clap::Command::new("remote"));
+
+        for shell in Shell::all() {
+            let mut buffer = Vec::new();
+            shell.generate_buffered(&mut command, &mut buffer);
+            assert_eq!(buffer, shell.generate_to_vec(&mut command), "{shell}");
+        }
+    }
+
+    #[cfg(feature = "bash")]
+    #[test]
+    fn bash_forms() {
+        let generate_form = |bash_form| {
+            let mut buffer = Vec::new();
+            Shell::Bash.generate_with_options(
+                &mut clap::Command::new("my-app"),
+                GenerateOptions::new().bash_form(bash_form),
+                &mut buffer,
+            );
+            String::from_utf8(buffer).unwrap()
+        };
+
+        assert_eq!(
+            generate_form(BashForm::Source),
+            generate(Shell::Bash, &mut clap::Command::new("my-app"))
+        );
+
+        let lazy_load = generate_form(BashForm::LazyLoad);
+        assert!(lazy_load.starts_with("_comp_cmd_my__app() {\n"));
+        assert!(lazy_load.contains("complete -F _comp_cmd_my__app "));
+        assert!(!lazy_load.contains(" _my__app"));
+
+        let out_dir = tempfile::tempdir().unwrap();
+        let path = Shell::Bash
+            .generate_to_with_options(
+                &mut clap::Command::new("my-app"),
+                GenerateOptions::new().bash_form(BashForm::LazyLoad),
+                out_dir.path(),
+            )
+            .unwrap();
+        // bash-completion's loader looks the file up by the command's name
+        assert_eq!(path, out_dir.path().join("my-app"));
+        assert_eq!(std::fs::read_to_string(path).unwrap(), lazy_load);
+        assert!(lazy_load.ends_with(
+            "complete -F _comp_cmd_my__app -o nosort -o bashdefault -o default my-app\nelse\n    complete -F _comp_cmd_my__app -o bashdefault -o default my-app\nfi\n"
+        ));
+    }
+
+    #[cfg(all(feature = "bash", feature = "powershell"))]
+    #[test]
+    fn install_method() {
+        assert_eq!(Shell::Bash.install_method(), InstallMethod::Eval);
+        assert_eq!(Shell::PowerShell.install_method(), InstallMethod::Eval);
+        #[cfg(feature = "nushell")]
+        assert_eq!(Shell::Nu.install_method(), InstallMethod::File);
+        #[cfg(feature = "fish")]
+        assert_eq!(Shell::Fish.install_method(), InstallMethod::File);
+
+        for shell in Shell::all() {
+            let evaluates = shell
+                .source_hint("app")
+                .is_some_and(|hint| hint.contains("app completions"));
+            assert_eq!(
+                shell.install_method() == InstallMethod::Eval,
+                evaluates,
+                "{shell}"
+            );
+
+            // The instructions show the one-liner for shells which evaluate the completions, and
+            // save them to a file for the others
+            let instructions = shell.install_instructions("app");
+            let generate = format!("app completions {}", shell.canonical_name());
+            match shell.install_method() {
+                InstallMethod::Eval => assert!(
+                    instructions.contains(&shell.source_hint("app").unwrap()),
+                    "{instructions}"
+                ),
+                InstallMethod::File => assert!(
+                    instructions.contains(&format!("{generate} > "))
+                        || instructions.contains(&format!("{generate} | save ")),
+                    "{instructions}"
+                ),
+            }
+        }
+    }
+
+    #[cfg(all(feature = "bash", feature = "fish"))]
+    #[test]
+    fn generate_to_creates_directories() {
+        let base_dir = tempfile::tempdir().unwrap();
+        let out_dir = base_dir.path().join("target/completions");
+        let mut command = clap::Command::new("app");
+
+        let path = Shell::Fish.generate_to(&mut command, &out_dir).unwrap();
+        assert_eq!(path, out_dir.join("app.fish"));
+        assert!(path.is_file());
+
+        let path = base_dir.path().join("staging/share/app");
+        Shell::Bash.generate_to_path(&mut command, &path).unwrap();
+        assert!(path.is_file());
+    }
+
+    #[cfg(feature = "bash")]
+    #[test]
+    fn possible_value_help() {
+        let bash = Shell::Bash.to_possible_value().unwrap();
+        assert_eq!(bash.get_name(), "bash");
+        assert_eq!(
+            bash.get_help().map(ToString::to_string).as_deref(),
+            Some("Bourne Again SHell")
+        );
+        assert!(Shell::all().iter().all(|shell| shell
+            .to_possible_value()
+            .unwrap()
+            .get_help()
+            .is_some()));
+
+        let help = clap::Command::new("app")
+            .subcommand(completions_subcommand())
+            .find_subcommand_mut("completions")
+            .unwrap()
+            .render_long_help()
+            .to_string();
+        assert!(help.contains("- bash:"), "{help}");
+        assert!(help.contains("Bourne Again SHell"), "{help}");
+    }
+
+    #[cfg(all(feature = "elvish", feature = "powershell"))]
+    #[test]
+    fn value_parser_hiding() {
+        let command = completions_subcommand().mut_arg("shell", |arg| {
+            arg.value_parser(Shell::value_parser_hiding(&[
+                Shell::Elvish,
+                Shell::PowerShell,
+            ]))
+        });
+
+        let names = command
+            .get_arguments()
+            .find(|arg| arg.get_id() == "shell")
+            .unwrap()
+            .get_possible_values()
+            .into_iter()
+            .filter(|value| !value.is_hide_set())
+            .map(|value| value.get_name().to_owned())
+            .collect::<Vec<_>>();
+        assert!(names.contains(&"bash".to_owned()));
+        assert!(!names.contains(&"elvish".to_owned()));
+        assert!(!names.contains(&"powershell".to_owned()));
+
+        let help = command.clone().render_long_help().to_string();
+        assert!(help.contains("- bash:"), "{help}");
+        assert!(!help.contains("elvish"), "{help}");
+
+        let matches = command
+            .clone()
+            .get_matches_from(["completions", "powershell"]);
+        assert_eq!(matches.get_one::<Shell>("shell"), Some(&Shell::PowerShell));
+        assert!(command
+            .clone()
+            .try_get_matches_from(["completions", "ksh"])
+            .is_err());
+
+        let matches = command
+            .mut_arg("shell", |arg| arg.ignore_case(true))
+            .get_matches_from(["completions", "ELVISH"]);
+        assert_eq!(matches.get_one::<Shell>("shell"), Some(&Shell::Elvish));
+    }
+
+    #[test]
+    fn generate_and_check() {
+        for &shell in Shell::all() {
+            let mut command = clap::Command::new("my-cli")
+                .arg(clap::Arg::new("verbose").long("verbose"))
+                .subcommand(clap::Command::new("serve"))
+                .subcommand(clap::Command::new("secret").hide(true));
+            let completions = shell.generate_and_check(&mut command).unwrap();
+            assert_eq!(completions, generate(shell, &mut command), "{shell}");
+        }
+    }
+
+    #[test]
+    fn generate_and_check_fails_for_degenerate_command() {
+        for &shell in Shell::all() {
+            let error = shell
+                .generate_and_check(&mut clap::Command::new(""))
+                .unwrap_err();
+            assert!(
+                matches!(&error, CheckError::MissingBinName(bin_name) if bin_name.is_empty()),
+                "{shell}: {error:?}"
+            );
+            assert_eq!(error.to_string(), "the command has an empty bin name");
+        }
+    }
+
+    #[test]
+    fn check_errors() {
+        let command = clap::Command::new("my-cli").subcommand(clap::Command::new("serve"));
+        assert!(matches!(
+            check::check(" \n", &command, "my-cli"),
+            Err(CheckError::Empty)
+        ));
+        assert!(matches!(
+            check::check("complete other", &command, "my-cli"),
+            Err(CheckError::MissingBinName(bin_name)) if bin_name == "my-cli"
+        ));
+        let error = check::check("complete my-cli", &command, "my-cli").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the completions don't mention the subcommand `serve`"
+        );
+        assert!(check::check("complete my-cli serve", &command, "my-cli").is_ok());
+    }
+
+    #[cfg(feature = "zsh")]
+    #[test]
+    fn run_completions_writes_output() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let path = out_dir.path().join("completions").join("_app");
+        let build_cli = || clap::Command::new("app").subcommand(completions_subcommand());
+
+        let matches = build_cli().get_matches_from([
+            std::ffi::OsStr::new("app"),
+            std::ffi::OsStr::new("completions"),
+            std::ffi::OsStr::new("zsh"),
+            std::ffi::OsStr::new("--output"),
+            path.as_os_str(),
+        ]);
+        let Some(("completions", sub_matches)) = matches.subcommand() else {
+            panic!("expected the `completions` subcommand");
+        };
+        assert_eq!(sub_matches.get_one::<PathBuf>("output"), Some(&path));
+        super::run_completions(sub_matches, &mut build_cli()).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            generate(Shell::Zsh, &mut build_cli())
+        );
+    }
+
+    #[test]
+    fn powershell_profile_path() {
+        with_vars(
+            &[("HOME", Some("/home/user")), ("XDG_CONFIG_HOME", None)],
+            || {
+                assert_eq!(
+                    PowerShellEdition::Core.profile_path_on(false),
+                    Some(PathBuf::from(
+                        "/home/user/.config/powershell/Microsoft.PowerShell_profile.ps1"
+                    ))
+                );
+                assert_eq!(PowerShellEdition::Windows.profile_path_on(false), None);
+
+                let core = PowerShellEdition::Core.profile_path_on(true).unwrap();
+                let windows = PowerShellEdition::Windows.profile_path_on(true).unwrap();
+                assert_ne!(core, windows);
+                assert!(core.ends_with("Documents/PowerShell/Microsoft.PowerShell_profile.ps1"));
+                assert!(windows
+                    .ends_with("Documents/WindowsPowerShell/Microsoft.PowerShell_profile.ps1"));
+
+                #[cfg(not(windows))]
+                assert_eq!(
+                    PowerShellEdition::Core.profile_path(),
+                    PowerShellEdition::Core.profile_path_on(false)
+                );
+            },
+        );
+    }
+
+    #[cfg(feature = "powershell")]
+    #[test]
+    fn powershell_install() {
+        let home = tempfile::tempdir().unwrap();
+        let home_path = home.path().to_str().unwrap();
+        let mut command = clap::Command::new("app");
+
+        with_vars(
+            &[("HOME", Some(home_path)), ("XDG_CONFIG_HOME", None)],
+            || {
+                let core = PowerShellEdition::Core
+                    .install_on(&mut command, true)
+                    .unwrap();
+                assert_eq!(core, home.path().join("Documents/PowerShell/_app.ps1"));
+                let windows = PowerShellEdition::Windows
+                    .install_on(&mut command, true)
+                    .unwrap();
+                assert_eq!(
+                    windows,
+                    home.path().join("Documents/WindowsPowerShell/_app.ps1")
+                );
+                assert_eq!(
+                    std::fs::read_to_string(windows).unwrap(),
+                    generate(Shell::PowerShell, &mut command)
+                );
+
+                assert_eq!(
+                    PowerShellEdition::Core
+                        .install_on(&mut command, false)
+                        .unwrap(),
+                    home.path().join(".config/powershell/_app.ps1")
+                );
+                assert_eq!(
+                    PowerShellEdition::Windows
+                        .install_on(&mut command, false)
+                        .unwrap_err()
+                        .kind(),
+                    std::io::ErrorKind::Unsupported
+                );
+            },
+        );
+    }
+
+    #[cfg(feature = "powershell")]
+    #[test]
+    fn powershell_install_instructions() {
+        let core = PowerShellEdition::Core.install_instructions("app");
+        assert_eq!(
+            core,
+            r"Add this to PowerShell 7's profile, $PROFILE:
+
+    # in $PROFILE
+    app completions powershell | Out-String | Invoke-Expression
+
+The profile is at:
+
+    ~\Documents\PowerShell\Microsoft.PowerShell_profile.ps1 on Windows
+    ~/.config/powershell/Microsoft.PowerShell_profile.ps1 elsewhere
+"
+        );
+
+        let windows = PowerShellEdition::Windows.install_instructions("app");
+        assert!(windows.starts_with("Add this to Windows PowerShell's profile, $PROFILE:\n"));
+        assert!(windows.ends_with(
+            "\n    ~\\Documents\\WindowsPowerShell\\Microsoft.PowerShell_profile.ps1\n"
+        ));
+        assert!(!windows.contains(".config"));
+
+        let shell = Shell::PowerShell.install_instructions("app");
+        assert!(shell.starts_with("Add this to your PowerShell profile, $PROFILE:\n"));
+        assert!(!shell.contains("Microsoft.PowerShell_profile.ps1"));
+    }
+
+    #[cfg(feature = "elvish")]
+    #[test]
+    fn elvish_forms() {
+        fn command() -> clap::Command {
+            clap::Command::new("app")
+                .disable_help_flag(true)
+                .disable_help_subcommand(true)
+                .arg(
+                    clap::Arg::new("verbose")
+                        .long("verbose")
+                        .help("Be loud")
+                        .action(clap::ArgAction::SetTrue),
+                )
+        }
+        fn generate_elvish(elvish_form: ElvishForm) -> String {
+            let mut buffer = Vec::new();
+            Shell::Elvish.generate_with_options(
+                &mut command(),
+                GenerateOptions::new().elvish_form(elvish_form),
+                &mut buffer,
+            );
+            String::from_utf8(buffer).unwrap()
+        }
+
+        assert_eq!(
+            generate_elvish(ElvishForm::Script),
+            generate(Shell::Elvish, &mut command())
+        );
+        assert_eq!(
+            generate_elvish(ElvishForm::Module),
+            "
+use builtin;
+use str;
+
+fn complete {|@words|
+    fn spaces {|n|
+        builtin:repeat $n ' ' | str:join ''
+    }
+    fn cand {|text desc|
+        edit:complex-candidate $text &display=$text' '(spaces (- 14 (wcswidth $text)))$desc
+    }
+    var command = 'app'
+    for word $words[1..-1] {
+        if (str:has-prefix $word '-') {
+            break
+        }
+        set command = $command';'$word
+    }
+    var completions = [
+        &'app'= {
+            cand --verbose 'Be loud'
+        }
+    ]
+    $completions[$command]
+}
+
+set edit:completion:arg-completer[app] = $complete~
+",
+        );
+    }
+
+    #[cfg(all(feature = "bash", feature = "zsh"))]
+    #[test]
+    fn canonical_name() {
+        const NAMES: [&str; 2] = [Shell::Bash.canonical_name(), Shell::Zsh.canonical_name()];
+        assert_eq!(NAMES, ["bash", "zsh"]);
+
+        for shell in Shell::value_variants() {
+            assert_eq!(
+                shell.canonical_name(),
+                shell.to_possible_value().unwrap().get_name()
+            );
+        }
+    }
+
+    #[test]
+    fn generate_all_to_out_dir() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let out_dir = out_dir.path().join("out");
+        let build_cli = || clap::Command::new("app").subcommand(clap::Command::new("serve"));
+
+        let paths = Shell::generate_all_to(&mut build_cli(), &out_dir).unwrap();
+        // A rebuild overwrites the files
+        assert_eq!(
+            Shell::generate_all_to(&mut build_cli(), &out_dir).unwrap(),
+            paths
+        );
+
+        for (shell, path) in Shell::all().iter().zip(&paths) {
+            assert_eq!(path.parent(), Some(out_dir.as_path()));
+            assert_eq!(
+                std::fs::read_to_string(path).unwrap(),
+                generate(*shell, &mut build_cli()),
+                "{shell}"
+            );
+        }
+    }
+
+    #[test]
+    fn includes_aliases() {
+        fn command() -> clap::Command {
+            clap::Command::new("app")
+                .arg(
+                    clap::Arg::new("verbose")
+                        .long("verbose")
+                        .visible_alias("noisy"),
+                )
+                .subcommand(
+                    clap::Command::new("checkout")
+                        .visible_alias("qco")
+                        .subcommand(clap::Command::new("branch").visible_alias("qbr")),
+                )
+        }
+
+        for &shell in Shell::all() {
+            let completions = generate(shell, &mut command());
+            for alias in ["noisy", "qco", "qbr"] {
+                assert_eq!(
+                    completions.contains(alias),
+                    shell.includes_aliases(),
+                    "{shell}: {alias}"
+                );
+            }
+        }
+
+        #[cfg(feature = "nushell")]
+        {
+            let completions = generate(Shell::Nu, &mut command());
+            for path in [
+                "app qco",
+                "app checkout qbr",
+                "app qco branch",
+                "app qco qbr",
+            ] {
+                assert!(
+                    completions.contains(&format!("export extern \"{path}\" [")),
+                    "{path}"
+                );
+            }
+        }
+    }
+
+    #[cfg(all(feature = "bash", feature = "powershell"))]
+    #[test]
+    fn powershell_bom() {
+        fn generate_with(shell: Shell, options: GenerateOptions) -> Vec<u8> {
+            let mut command = clap::Command::new("app").about("Ünïcode");
+            let mut buffer = Vec::new();
+            shell.generate_with_options(&mut command, options, &mut buffer);
+            buffer
+        }
+
+        let plain = generate_with(Shell::PowerShell, GenerateOptions::new());
+        assert!(!plain.starts_with(&[0xEF, 0xBB, 0xBF]));
+
+        let with_bom = generate_with(
+            Shell::PowerShell,
+            GenerateOptions::new().powershell_bom(true),
+        );
+        assert_eq!(with_bom[..3], [0xEF, 0xBB, 0xBF]);
+        assert_eq!(with_bom[3..], plain);
+
+        let with_header = generate_with(
+            Shell::PowerShell,
+            GenerateOptions::new()
+                .powershell_bom(true)
+                .version_header(true),
+        );
+        assert!(String::from_utf8(with_header)
+            .unwrap()
+            .starts_with("\u{feff}# generated by app"));
+
+        assert_eq!(
+            generate_with(Shell::Bash, GenerateOptions::new().powershell_bom(true)),
+            generate_with(Shell::Bash, GenerateOptions::new())
+        );
+    }
+
+    #[cfg(all(feature = "bash", feature = "zsh"))]
+    #[test]
+    fn feature_name() {
+        assert_eq!(Shell::Bash.feature_name(), Some("bash"));
+        assert_eq!(Shell::Zsh.feature_name(), Some("zsh"));
+        #[cfg(feature = "fig")]
+        assert_eq!(Shell::Fig.feature_name(), Some("fig"));
+        #[cfg(feature = "nushell")]
+        assert_eq!(Shell::Nu.feature_name(), Some("nushell"));
+
+        // Each feature enables one shell, named after it
+        for shell in Shell::all() {
+            assert_eq!(shell.feature_name(), Some(shell.canonical_name()));
+        }
+    }
+
+    #[cfg(feature = "bash")]
+    #[test]
+    fn generate_subcommand() {
+        let mut command = clap::Command::new("app")
+            .arg(
+                clap::Arg::new("verbose")
+                    .long("verbose")
+                    .global(true)
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .subcommand(
+                clap::Command::new("remote")
+                    .subcommand(
+                        clap::Command::new("add")
+                            .arg(clap::Arg::new("force").long("force"))
+                            .subcommand(clap::Command::new("mirror")),
+                    )
+                    .subcommand(clap::Command::new("remove")),
+            );
+
+        for &shell in Shell::all() {
+            let mut buffer = Vec::new();
+            shell
+                .generate_subcommand(&mut command, &["remote", "add"], &mut buffer)
+                .unwrap();
+            let completions = String::from_utf8(buffer).unwrap();
+
+            assert!(completions.contains("app-remote-add"), "{shell}");
+            assert!(completions.contains("mirror"), "{shell}");
+            assert!(!completions.contains("remove"), "{shell}");
+            assert!(completions.contains("force"), "{shell}");
+            assert!(completions.contains("verbose"), "{shell}");
+        }
+
+        let mut buffer = Vec::new();
+        Shell::Bash
+            .generate_subcommand(&mut command.clone(), &[], &mut buffer)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            generate(Shell::Bash, &mut command)
+        );
+
+        let error = Shell::Bash
+            .generate_subcommand(&mut command, &["remote", "rename"], &mut Vec::new())
+            .unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        assert_eq!(error.to_string(), "`app remote` has no subcommand `rename`");
+    }
+
+    #[cfg(all(
+        feature = "bash",
+        not(any(
+            feature = "carapace",
+            feature = "clink",
+            feature = "elvish",
+            feature = "fig",
+            feature = "fish",
+            feature = "man",
+            feature = "murex",
+            feature = "nushell",
+            feature = "powershell",
+            feature = "tcsh",
+            feature = "xonsh",
+            feature = "zsh",
+        ))
+    ))]
+    #[test]
+    fn bash_only_build() {
+        assert_eq!(Shell::names(), ["bash"]);
+        assert_eq!(Shell::value_variants(), [Shell::Bash]);
+        assert_eq!(Shell::default(), Shell::Bash);
+        assert!(Shell::from_str("zsh", false).is_err());
+        assert_eq!(Shell::try_from(clap_complete::Shell::Bash), Ok(Shell::Bash));
+
+        let error = Shell::try_from(clap_complete::Shell::Zsh).unwrap_err();
+        assert_eq!(error.shell(), clap_complete::Shell::Zsh);
+        assert_eq!(error.feature_name(), Some("zsh"));
+        assert_eq!(
+            error.to_string(),
+            "the `zsh` shell needs clap_complete_command's `zsh` feature"
+        );
+    }
+
+    #[cfg(feature = "fish")]
+    #[test]
+    fn from_matches() {
+        let command = clap::Command::new("app")
+            .arg(clap::Arg::new("verbose").long("verbose"))
+            .subcommand(completions_subcommand())
+            .subcommand(clap::Command::new("serve"));
+
+        let matches = command
+            .clone()
+            .get_matches_from(["app", "completions", "fish"]);
+        assert_eq!(Shell::from_matches(&matches), Some(Shell::Fish));
+        let (_, sub_matches) = matches.subcommand().unwrap();
+        assert_eq!(Shell::from_matches(sub_matches), Some(Shell::Fish));
+
+        let matches = command.clone().get_matches_from(["app", "serve"]);
+        assert_eq!(Shell::from_matches(&matches), None);
+        let matches = command.get_matches_from(["app", "--verbose", "yes"]);
+        assert_eq!(Shell::from_matches(&matches), None);
+    }
+
+    #[test]
+    fn value_parser_ignores_case() {
+        let command = completions_subcommand()
+            .mut_arg("shell", |arg| arg.value_parser(Shell::value_parser()));
+        command.clone().debug_assert();
+
+        for &shell in Shell::all() {
+            for name in [
+                shell.canonical_name().to_owned(),
+                shell.canonical_name().to_uppercase(),
+            ] {
+                let matches = command
+                    .clone()
+                    .get_matches_from(["completions", name.as_str()]);
+                assert_eq!(matches.get_one::<Shell>("shell"), Some(&shell));
+            }
+        }
+        #[cfg(feature = "powershell")]
+        {
+            let matches = command
+                .clone()
+                .get_matches_from(["completions", "PowerShell"]);
+            assert_eq!(matches.get_one::<Shell>("shell"), Some(&Shell::PowerShell));
+        }
+
+        let error = command
+            .clone()
+            .try_get_matches_from(["completions", "KSH"])
+            .unwrap_err();
+        assert_eq!(error.kind(), clap::error::ErrorKind::InvalidValue);
+        assert!(error.to_string().contains("'KSH'"));
+
+        let help = command.clone().render_help().to_string();
+        assert!(help.contains(Shell::all()[0].canonical_name()));
+
+        // Without the parser, clap stays case-sensitive
+        assert!(completions_subcommand()
+            .try_get_matches_from(["completions", "BASH"])
+            .is_err());
+    }
+
+    #[cfg(any(feature = "nushell", feature = "powershell"))]
+    #[test]
+    fn aliases() {
+        fn parse(command: clap::Command, value: &str) -> Option<Shell> {
+            command
+                .try_get_matches_from(["completions", value])
+                .ok()?
+                .get_one::<Shell>("shell")
+                .copied()
+        }
+        let hiding = completions_subcommand().mut_arg("shell", |arg| {
+            arg.value_parser(Shell::value_parser_hiding(&[]))
+        });
+
+        #[cfg(feature = "powershell")]
+        for alias in ["ps", "pwsh", "PS"] {
+            let expected = (alias != "PS").then_some(Shell::PowerShell);
+            assert_eq!(parse(completions_subcommand(), alias), expected, "{alias}");
+            assert_eq!(parse(hiding.clone(), alias), expected, "{alias}");
+        }
+        #[cfg(feature = "nushell")]
+        {
+            assert_eq!(parse(completions_subcommand(), "nu"), Some(Shell::Nu));
+            assert_eq!(parse(hiding, "nu"), Some(Shell::Nu));
+            assert_eq!(Shell::Nu.to_string(), "nushell");
+            assert_eq!(
+                clap_complete::Generator::file_name(&Shell::Nu, "app"),
+                "app.nu"
+            );
+        }
+
+        let help = completions_subcommand().render_help().to_string();
+        assert!(!help.contains("pwsh"));
+    }
+
+    #[test]
+    fn generate_to_with_name() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let shell = Shell::all()[0];
+        let mut command = clap::Command::new("app");
+
+        let path = shell
+            .generate_to_with_name(&mut command, out_dir.path(), |bin_name| {
+                bin_name.to_uppercase()
+            })
+            .unwrap();
+
+        assert_eq!(path, out_dir.path().join("APP"));
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            generate(shell, &mut command)
+        );
+
+        let error = shell
+            .generate_to_with_name(&mut clap::Command::new(""), out_dir.path(), |_| {
+                unreachable!("the bin name is empty")
+            })
+            .unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn supports_dynamic() {
+        #[cfg(feature = "bash")]
+        assert!(Shell::Bash.supports_dynamic());
+        #[cfg(feature = "powershell")]
+        assert!(Shell::PowerShell.supports_dynamic());
+        #[cfg(feature = "nushell")]
+        assert!(!Shell::Nu.supports_dynamic());
+        #[cfg(feature = "fig")]
+        assert!(!Shell::Fig.supports_dynamic());
+
+        #[cfg(feature = "dynamic")]
+        for &shell in Shell::all() {
+            let result =
+                dynamic::write_registration(shell, &clap::Command::new("app"), &mut Vec::new());
+            assert_eq!(result.is_ok(), shell.supports_dynamic(), "{shell}");
+        }
+    }
+
+    #[cfg(feature = "bash")]
+    #[test]
+    fn completions_subcommand_with_aliases_parses_alias() {
+        let build_cli = || {
+            clap::Command::new("app").subcommand(completions_subcommand_with_aliases(&[
+                "completion",
+                "complete",
+            ]))
+        };
+        build_cli().debug_assert();
+
+        for name in ["completions", "completion", "complete"] {
+            let matches = build_cli().get_matches_from(["app", name, "bash"]);
+            assert_eq!(Shell::from_matches(&matches), Some(Shell::Bash), "{name}");
+
+            let mut buffer = Vec::new();
+            assert!(super::run(&matches, &mut build_cli(), &mut buffer).unwrap());
+            assert_eq!(
+                String::from_utf8(buffer).unwrap(),
+                generate(Shell::Bash, &mut build_cli())
+            );
+        }
+
+        let help = build_cli().render_help().to_string();
+        assert!(!help.contains("aliases"));
+    }
+
+    #[cfg(feature = "clink")]
+    #[test]
+    fn clink() {
+        let mut command = clap::Command::new("app")
+            .disable_help_flag(true)
+            .disable_help_subcommand(true)
+            .arg(
+                clap::Arg::new("verbose")
+                    .short('v')
+                    .long("verbose")
+                    .help("Print more")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                clap::Arg::new("color")
+                    .long("color")
+                    .value_parser(["always", "never"]),
+            )
+            .subcommand(
+                clap::Command::new("build")
+                    .about("Build a \"target\"")
+                    .visible_alias("b")
+                    .disable_help_flag(true)
+                    .arg(
+                        clap::Arg::new("release")
+                            .long("release")
+                            .action(clap::ArgAction::SetTrue),
+                    )
+                    .arg(clap::Arg::new("manifest").value_hint(clap::ValueHint::FilePath)),
+            )
+            .subcommand(
+                clap::Command::new("clean")
+                    .about("Remove build artifacts")
+                    .disable_help_flag(true),
+            );
+
+        assert_eq!(
+            generate(Shell::Clink, &mut command),
+            "clink.argmatcher(\"app\")
+    :addarg({
+        \"build\" .. clink.argmatcher()
+            :addarg(clink.filematches)
+            :addflags({
+                \"--release\",
+            }),
+        \"b\" .. clink.argmatcher()
+            :addarg(clink.filematches)
+            :addflags({
+                \"--release\",
+            }),
+        \"clean\" .. clink.argmatcher()
+            :nofiles(),
+    })
+    :addflags({
+        \"--verbose\",
+        \"-v\",
+        \"--color\" .. clink.argmatcher():addarg({ \"always\", \"never\" }),
+    })
+    :adddescriptions({
+        [\"--verbose\"] = \"Print more\",
+        [\"-v\"] = \"Print more\",
+        [\"build\"] = \"Build a \\\"target\\\"\",
+        [\"b\"] = \"Build a \\\"target\\\"\",
+        [\"clean\"] = \"Remove build artifacts\",
+    })
+    :nofiles()
+",
+        );
+    }
+
+    #[test]
+    fn completable_subcommands() {
+        let command = clap::Command::new("app")
+            .disable_help_subcommand(true)
+            .subcommand(clap::Command::new("build").visible_alias("b"))
+            .subcommand(clap::Command::new("debug").hide(true));
+        assert_eq!(super::completable_subcommands(&command), ["build", "b"]);
+
+        let command = clap::Command::new("app").subcommand(clap::Command::new("build"));
+        assert_eq!(super::completable_subcommands(&command), ["build", "help"]);
+
+        let mut command = clap::Command::new("app")
+            .subcommand(
+                clap::Command::new("compile")
+                    .visible_alias("cmpl")
+                    .alias("cc"),
+            )
+            .subcommand(
+                clap::Command::new("inspect")
+                    .hide(true)
+                    .visible_alias("insp"),
+            );
+        let subcommands = super::completable_subcommands(&command);
+        assert_eq!(subcommands, ["compile", "cmpl", "help"]);
+        for shell in Shell::value_variants() {
+            let script = generate(*shell, &mut command);
+            assert!(script.contains("compile"), "{shell:?}");
+            assert!(
+                !shell.includes_aliases() || script.contains("cmpl"),
+                "{shell:?}"
+            );
+            for hidden in ["inspect", "insp"] {
+                assert!(!script.contains(hidden), "{shell:?} completes {hidden}");
+            }
+        }
+    }
+
+    #[test]
+    fn generate_to_with_policy() {
+        let shell = Shell::all()[0];
+        let out_dir = tempfile::tempdir().unwrap();
+        let path = out_dir.path().join(shell.file_name("app"));
+        let generate_with_policy = |policy| {
+            std::fs::write(&path, "customised").unwrap();
+            let written = shell.generate_to_with_policy(
+                &mut clap::Command::new("app"),
+                out_dir.path(),
+                policy,
+            );
+            (written, std::fs::read_to_string(&path).unwrap())
+        };
+
+        let (written, contents) = generate_with_policy(OverwritePolicy::Overwrite);
+        assert_eq!(written.unwrap(), Some(path.clone()));
+        assert_eq!(contents, generate(shell, &mut clap::Command::new("app")));
+
+        let (written, contents) = generate_with_policy(OverwritePolicy::SkipIfExists);
+        assert_eq!(written.unwrap(), None);
+        assert_eq!(contents, "customised");
+
+        let (written, contents) = generate_with_policy(OverwritePolicy::ErrorIfExists);
+        assert_eq!(
+            written.unwrap_err().kind(),
+            std::io::ErrorKind::AlreadyExists
+        );
+        assert_eq!(contents, "customised");
+
+        std::fs::remove_file(&path).unwrap();
+        for policy in [
+            OverwritePolicy::SkipIfExists,
+            OverwritePolicy::ErrorIfExists,
+        ] {
+            let written = shell
+                .generate_to_with_policy(&mut clap::Command::new("app"), out_dir.path(), policy)
+                .unwrap();
+            assert_eq!(written, Some(path.clone()));
+            assert_eq!(
+                std::fs::read_to_string(&path).unwrap(),
+                generate(shell, &mut clap::Command::new("app"))
+            );
+            std::fs::remove_file(&path).unwrap();
+        }
+    }
+
+    #[test]
+    fn conflicts_with_hidden_args() {
+        let mut command = clap::Command::new("app")
+            .arg(clap::Arg::new("debug").long("debug").hide(true))
+            .arg(
+                clap::Arg::new("quiet")
+                    .long("quiet")
+                    .conflicts_with("debug"),
+            )
+            .group(clap::ArgGroup::new("output").args(["debug", "quiet"]));
+        for shell in Shell::value_variants() {
+            let script = generate(*shell, &mut command);
+            assert!(script.contains("quiet"), "{shell:?}");
+            assert!(!script.contains("debug"), "{shell:?}");
+        }
     }
 }
@@ -0,0 +1,149 @@
+//! A generator for murex, as no crate generates its completions.
+//!
+//! murex's `autocomplete set` takes a JSON array describing the parameters of a command. Options and
+//! subcommands are listed with their descriptions in `FlagsDesc`, and `FlagValues` describes what
+//! follows each of them: an option's value, or a subcommand's own parameters.
+
+use clap::ValueHint;
+
+use crate::walk;
+
+pub fn generate(command: &clap::Command, buffer: &mut dyn std::io::Write) {
+    let bin_name = command.get_bin_name().unwrap_or_else(|| command.get_name());
+
+    let script = format!(
+        "autocomplete set {} {{ {} }}\n",
+        bin_name,
+        parameters(command, 0)
+    );
+
+    buffer
+        .write_all(script.as_bytes())
+        .expect("failed to write completion file");
+}
+
+/// The JSON array of `command`'s parameters, indented by `depth` levels
+fn parameters(command: &clap::Command, depth: usize) -> String {
+    let mut flags_desc = Vec::new();
+    let mut flag_values = Vec::new();
+
+    for arg in walk::options(command) {
+        let description = arg.get_help().map(ToString::to_string).unwrap_or_default();
+        let value = arg
+            .get_action()
+            .takes_values()
+            .then(|| value_parameters(arg, depth + 3));
+        for name in walk::option_names(arg) {
+            flags_desc.push((name.clone(), json_string(&description)));
+            if let Some(value) = &value {
+                flag_values.push((name, value.clone()));
+            }
+        }
+    }
+
+    for subcommand in walk::subcommands(command) {
+        let description = subcommand
+            .get_about()
+            .map(ToString::to_string)
+            .unwrap_or_default();
+        let value = parameters(subcommand, depth + 3);
+        for name in walk::subcommand_names(subcommand) {
+            flags_desc.push((name.to_owned(), json_string(&description)));
+            flag_values.push((name.to_owned(), value.clone()));
+        }
+    }
+
+    let positionals = walk::positionals(command).collect::<Vec<_>>();
+    let values = positionals
+        .iter()
+        .flat_map(|arg| walk::possible_values(arg))
+        .collect::<Vec<_>>();
+
+    let mut fields = Vec::new();
+    if !values.is_empty() {
+        fields.push(("Flags".to_owned(), json_list(&values)));
+    }
+    if !flags_desc.is_empty() {
+        fields.push(("FlagsDesc".to_owned(), json_object(&flags_desc, depth + 2)));
+    }
+    if !flag_values.is_empty() {
+        fields.push((
+            "FlagValues".to_owned(),
+            json_object(&flag_values, depth + 2),
+        ));
+    }
+    if positionals.iter().any(|arg| walk::completes_files(arg)) {
+        fields.push(("IncFiles".to_owned(), "true".to_owned()));
+    }
+    if !flags_desc.is_empty() || positionals.len() > 1 {
+        fields.push(("AllowMultiple".to_owned(), "true".to_owned()));
+    }
+    fields.push(("Optional".to_owned(), "true".to_owned()));
+
+    parameter_array(&fields, depth)
+}
+
+/// The JSON array completing the value of the option `arg`
+fn value_parameters(arg: &clap::Arg, depth: usize) -> String {
+    let values = walk::possible_values(arg);
+    let field = if !values.is_empty() {
+        ("Flags", json_list(&values))
+    } else if arg.get_value_hint() == ValueHint::DirPath {
+        ("IncDirs", "true".to_owned())
+    } else if walk::completes_files(arg) {
+        ("IncFiles", "true".to_owned())
+    } else {
+        ("AllowAny", "true".to_owned())
+    };
+    parameter_array(&[(field.0.to_owned(), field.1)], depth)
+}
+
+/// A JSON array with one parameter object of `fields`
+fn parameter_array(fields: &[(String, String)], depth: usize) -> String {
+    let indent = "    ".repeat(depth);
+    format!(
+        "[\n{indent}    {}\n{indent}]",
+        json_object(fields, depth + 1)
+    )
+}
+
+/// A JSON object of `fields`, whose values are already JSON, each on its own line indented one
+/// level more than `depth`
+fn json_object(fields: &[(String, String)], depth: usize) -> String {
+    let indent = "    ".repeat(depth);
+    let fields = fields
+        .iter()
+        .map(|(key, value)| format!("{indent}    {}: {value}", json_string(key)))
+        .collect::<Vec<_>>();
+    format!("{{\n{}\n{indent}}}", fields.join(",\n"))
+}
+
+fn json_list(values: &[String]) -> String {
+    let values = values
+        .iter()
+        .map(|value| json_string(value))
+        .collect::<Vec<_>>();
+    format!("[ {} ]", values.join(", "))
+}
+
+fn json_string(value: &str) -> String {
+    let mut string = String::from("\"");
+    for character in value.chars() {
+        match character {
+            '\\' => string.push_str("\\\\"),
+            '"' => string.push_str("\\\""),
+            '\n' => string.push_str("\\n"),
+            '\r' => string.push_str("\\r"),
+            '\t' => string.push_str("\\t"),
+            // Braces in strings are escaped so the `autocomplete set` block stays balanced
+            '{' => string.push_str("\\u007b"),
+            '}' => string.push_str("\\u007d"),
+            character if character.is_control() => {
+                string.push_str(&format!("\\u{:04x}", u32::from(character)));
+            }
+            _ => string.push(character),
+        }
+    }
+    string.push('"');
+    string
+}
@@ -0,0 +1,83 @@
+//! Adjustments on top of [`clap_complete_nushell::Nushell`]'s output.
+
+use clap_complete::Generator;
+
+const MODULE_START: &str = "module completions {\n";
+const MODULE_END: &str = "}\n\nexport use completions *\n";
+
+pub fn generate(command: &clap::Command, buffer: &mut dyn std::io::Write) {
+    let mut script = Vec::new();
+    clap_complete_nushell::Nushell.generate(command, &mut script);
+    let script = String::from_utf8(script).expect("nushell completions should be valid UTF-8");
+
+    let script = with_aliases(command, &script);
+
+    buffer
+        .write_all(script.as_bytes())
+        .expect("failed to write completion file");
+}
+
+/// Repeats the externs of subcommands with visible aliases for each alias, which
+/// `clap_complete_nushell` leaves out, e.g. `app co` after `app checkout`
+fn with_aliases(command: &clap::Command, script: &str) -> String {
+    script
+        .split("\n\n")
+        .flat_map(|block| {
+            let Some(path) = block.lines().find_map(|line| {
+                line.trim_start()
+                    .strip_prefix("export extern \"")?
+                    .strip_suffix("\" [")
+            }) else {
+                return vec![block.to_owned()];
+            };
+            let mut segments = path.split(' ');
+            let bin_name = segments.next().unwrap_or_default();
+            alias_paths(command, segments.collect::<Vec<_>>().as_slice())
+                .into_iter()
+                .map(|alias_path| {
+                    block.replacen(
+                        &format!("extern \"{path}\""),
+                        &format!("extern \"{bin_name}{alias_path}\""),
+                        1,
+                    )
+                })
+                .collect()
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// The paths, each segment preceded by a space, of the subcommand at `path` under `command` when
+/// each subcommand is called by its name or one of its visible aliases, starting with the names
+fn alias_paths(command: &clap::Command, path: &[&str]) -> Vec<String> {
+    let Some((name, rest)) = path.split_first() else {
+        return vec![String::new()];
+    };
+    let Some(subcommand) = command.find_subcommand(name) else {
+        return vec![path.iter().map(|segment| format!(" {segment}")).collect()];
+    };
+    let rest = alias_paths(subcommand, rest);
+    std::iter::once(subcommand.get_name())
+        .chain(subcommand.get_visible_aliases())
+        .flat_map(|name| rest.iter().map(move |rest| format!(" {name}{rest}")))
+        .collect()
+}
+
+/// Unwraps the `completions` module so the script is a module of its own, for `overlay use`
+pub fn overlay(script: &str) -> String {
+    let Some(body) = script
+        .strip_prefix(MODULE_START)
+        .and_then(|script| script.strip_suffix(MODULE_END))
+    else {
+        return script.to_owned();
+    };
+
+    let mut module = body
+        .trim_matches('\n')
+        .lines()
+        .map(|line| line.strip_prefix("  ").unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join("\n");
+    module.push('\n');
+    module
+}
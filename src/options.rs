@@ -0,0 +1,555 @@
+use crate::Shell;
+
+/// Options for [`Shell::generate_with_options()`].
+///
+/// The defaults produce the same completions as [`Shell::generate()`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use clap::Command;
+/// use clap_complete_command::{GenerateOptions, Shell};
+///
+/// let mut command = Command::new("cli");
+/// let options = GenerateOptions::new().display_order(true);
+/// Shell::default().generate_with_options(&mut command, options, &mut std::io::stdout());
+/// ```
+#[derive(Clone, Debug)]
+pub struct GenerateOptions {
+    #[cfg(feature = "bash")]
+    bash_form: BashForm,
+    display_order: bool,
+    #[cfg(feature = "elvish")]
+    elvish_form: ElvishForm,
+    include_hidden: bool,
+    #[cfg(feature = "nushell")]
+    nu_form: NuForm,
+    #[cfg(feature = "powershell")]
+    powershell_bom: bool,
+    version_header: bool,
+    value_names: bool,
+    #[cfg(feature = "fish")]
+    fish_help_and_version: bool,
+    #[cfg(feature = "zsh")]
+    zsh_form: ZshForm,
+    #[cfg(any(feature = "bash", feature = "zsh"))]
+    function_prefix: Option<String>,
+    #[cfg(feature = "fig")]
+    fig_icon: Option<String>,
+    #[cfg(feature = "fig")]
+    fig_long_about: bool,
+}
+
+/// Which bash-completion version [`Shell::Bash`] completions are for, see
+/// [`GenerateOptions::bash_form()`].
+#[cfg(feature = "bash")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BashForm {
+    /// A script which registers its completion function with `complete` when it's sourced, e.g.
+    /// from `~/.bashrc` or bash-completion 1.x's eagerly loaded `/etc/bash_completion.d`
+    #[default]
+    Source,
+    /// A script for bash-completion 2.x's `completions` directories, which bash-completion sources
+    /// the first time the command is completed, like [`Shell::install()`] writes
+    ///
+    /// The file has to be named after the command, see [`GenerateOptions::file_name()`], and its
+    /// function is named `_comp_cmd_<bin>` by bash-completion's convention. Sourcing it registers
+    /// the function with `complete`, which is what bash-completion checks for after loading it.
+    LazyLoad,
+}
+
+/// How [`Shell::Elvish`] completions are loaded, see [`GenerateOptions::elvish_form()`].
+#[cfg(feature = "elvish")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ElvishForm {
+    /// A script which sets the command's `edit:completion:arg-completer` when it's evaluated, e.g.
+    /// `eval (cli completions elvish | slurp)` in `rc.elv`
+    #[default]
+    Script,
+    /// A module which defines the completer as its `complete` function and registers it when it's
+    /// imported, e.g. saved as `cli.elv` in `~/.config/elvish/lib` and loaded with `use cli` in
+    /// `rc.elv`, without evaluating the script on every start
+    Module,
+}
+
+/// How [`Shell::Nu`] completions are structured, see
+/// [`GenerateOptions::nu_form()`].
+#[cfg(feature = "nushell")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum NuForm {
+    /// The externs are wrapped in a `completions` module which is then exported, so the script
+    /// can be loaded with `source` from `config.nu`
+    #[default]
+    Source,
+    /// The externs are exported at the top level, so the script is a module of its own which can
+    /// be loaded with `use <file> *` or `overlay use <file>`, and unloaded with `overlay hide`
+    Overlay,
+}
+
+/// How [`Shell::Zsh`] completions are loaded, see [`GenerateOptions::zsh_form()`].
+#[cfg(feature = "zsh")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ZshForm {
+    /// A `_<bin>` function file starting with `#compdef`, which `compinit` autoloads when it's in a
+    /// directory on `$fpath`, like [`Shell::generate_to()`] writes
+    ///
+    /// The script also registers itself with `compdef` when it's sourced.
+    #[default]
+    Autoload,
+    /// A script calling `compdef` without a `#compdef` line, to be sourced from `.zshrc` after
+    /// `compinit`, e.g. `source <(cli completions zsh)`
+    Inline,
+}
+
+#[cfg_attr(not(feature = "fish"), allow(clippy::derivable_impls))]
+impl Default for GenerateOptions {
+    fn default() -> Self {
+        Self {
+            #[cfg(feature = "bash")]
+            bash_form: BashForm::default(),
+            display_order: false,
+            #[cfg(feature = "elvish")]
+            elvish_form: ElvishForm::default(),
+            include_hidden: false,
+            #[cfg(feature = "nushell")]
+            nu_form: NuForm::default(),
+            #[cfg(feature = "powershell")]
+            powershell_bom: false,
+            version_header: false,
+            value_names: false,
+            #[cfg(feature = "fish")]
+            fish_help_and_version: true,
+            #[cfg(feature = "zsh")]
+            zsh_form: ZshForm::default(),
+            #[cfg(any(feature = "bash", feature = "zsh"))]
+            function_prefix: None,
+            #[cfg(feature = "fig")]
+            fig_icon: None,
+            #[cfg(feature = "fig")]
+            fig_long_about: false,
+        }
+    }
+}
+
+impl GenerateOptions {
+    /// Options producing the same completions as [`Shell::generate()`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Which bash-completion version [`Shell::Bash`] completions are for.
+    ///
+    /// Defaults to [`BashForm::Source`].
+    #[cfg(feature = "bash")]
+    #[must_use]
+    pub const fn bash_form(mut self, bash_form: BashForm) -> Self {
+        self.bash_form = bash_form;
+        self
+    }
+
+    /// Order subcommands and options by their display order, then the order they were defined in,
+    /// matching `--help`.
+    ///
+    /// Defaults to `false`, keeping the order they were defined in.
+    /// Positional arguments always keep their order.
+    #[must_use]
+    pub const fn display_order(mut self, yes: bool) -> Self {
+        self.display_order = yes;
+        self
+    }
+
+    /// How [`Shell::Elvish`] completions are loaded.
+    ///
+    /// Defaults to [`ElvishForm::Script`].
+    #[cfg(feature = "elvish")]
+    #[must_use]
+    pub const fn elvish_form(mut self, elvish_form: ElvishForm) -> Self {
+        self.elvish_form = elvish_form;
+        self
+    }
+
+    /// Complete hidden subcommands, arguments, aliases and possible values like any other.
+    ///
+    /// Defaults to `false`, leaving them out of every shell's completions.
+    #[must_use]
+    pub const fn include_hidden(mut self, yes: bool) -> Self {
+        self.include_hidden = yes;
+        self
+    }
+
+    /// How [`Shell::Nu`] completions are structured.
+    ///
+    /// Defaults to [`NuForm::Source`].
+    #[cfg(feature = "nushell")]
+    #[must_use]
+    pub const fn nu_form(mut self, nu_form: NuForm) -> Self {
+        self.nu_form = nu_form;
+        self
+    }
+
+    /// Start [`Shell::PowerShell`] completions with a UTF-8 byte order mark, which Windows
+    /// PowerShell 5.1 needs to read a script saved to a file as UTF-8 rather than the system's
+    /// legacy code page, or non-ASCII help text is garbled.
+    ///
+    /// Defaults to `false`. PowerShell 7 reads UTF-8 either way, and completions evaluated from
+    /// the `completions` subcommand's output aren't read from a file, so only set it when writing
+    /// the completions to a file for Windows PowerShell.
+    #[cfg(feature = "powershell")]
+    #[must_use]
+    pub const fn powershell_bom(mut self, yes: bool) -> Self {
+        self.powershell_bom = yes;
+        self
+    }
+
+    /// Start the completions with a comment saying which version of the command generated them,
+    /// e.g. `# generated by cli 1.2.3`, using the command's bin name and [`clap::Command::version()`].
+    ///
+    /// Defaults to `false`.
+    #[must_use]
+    pub const fn version_header(mut self, yes: bool) -> Self {
+        self.version_header = yes;
+        self
+    }
+
+    /// Start the descriptions of options with their value names, e.g. `<SRC,DST> Bind mount` for
+    /// `--mount`, so the expected format is shown where the value can't be completed.
+    ///
+    /// Defaults to `false`. Only value names set with [`clap::Arg::value_name()`] or
+    /// [`clap::Arg::value_names()`] are added.
+    #[must_use]
+    pub const fn value_names(mut self, yes: bool) -> Self {
+        self.value_names = yes;
+        self
+    }
+
+    /// Whether [`Shell::Fish`] completes the `--help` and `--version` flags, which can clutter the
+    /// menus of CLIs with many nested subcommands.
+    ///
+    /// Defaults to `true`. The flags keep working when they aren't completed.
+    #[cfg(feature = "fish")]
+    #[must_use]
+    pub const fn fish_help_and_version(mut self, yes: bool) -> Self {
+        self.fish_help_and_version = yes;
+        self
+    }
+
+    /// How [`Shell::Zsh`] completions are loaded.
+    ///
+    /// Defaults to [`ZshForm::Autoload`].
+    #[cfg(feature = "zsh")]
+    #[must_use]
+    pub const fn zsh_form(mut self, zsh_form: ZshForm) -> Self {
+        self.zsh_form = zsh_form;
+        self
+    }
+
+    /// Namespace the shell functions of [`Shell::Bash`] and [`Shell::Zsh`] with `prefix`, e.g.
+    /// `_acme_cli` rather than `_cli`, so they don't clash with another tool's completions for a
+    /// command of the same name.
+    ///
+    /// Defaults to none. Other shells don't define functions named after the command, so they
+    /// ignore it. An autoloaded zsh function file has to be named after the function, e.g.
+    /// `_acme_cli` rather than the `_cli` [`Shell::generate_to()`] uses.
+    #[cfg(any(feature = "bash", feature = "zsh"))]
+    #[must_use]
+    pub fn function_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.function_prefix = Some(prefix.into());
+        self
+    }
+
+    /// The icon [`Shell::Fig`] shows next to the command and each of its subcommands, e.g. an emoji
+    /// or a `fig://` URL.
+    ///
+    /// Defaults to none, leaving it up to Fig.
+    #[cfg(feature = "fig")]
+    #[must_use]
+    pub fn fig_icon(mut self, icon: impl Into<String>) -> Self {
+        self.fig_icon = Some(icon.into());
+        self
+    }
+
+    /// Whether [`Shell::Fig`] describes the command and its subcommands with their
+    /// [`clap::Command::long_about()`], where they have one, rather than their
+    /// [`clap::Command::about()`].
+    ///
+    /// Defaults to `false`.
+    #[cfg(feature = "fig")]
+    #[must_use]
+    pub const fn fig_long_about(mut self, yes: bool) -> Self {
+        self.fig_long_about = yes;
+        self
+    }
+
+    /// The name of the file [`Shell::generate_to_with_options()`] writes the completions of
+    /// `bin_name` for `shell` to.
+    ///
+    /// This is [`Shell::file_name()`], except for [`BashForm::LazyLoad`], which is named `bin_name`
+    /// as bash-completion looks up the file to load by the command's name.
+    #[must_use]
+    pub fn file_name(&self, shell: Shell, bin_name: &str) -> String {
+        #[cfg(feature = "bash")]
+        if matches!(shell, Shell::Bash) && self.bash_form == BashForm::LazyLoad {
+            return bin_name.to_owned();
+        }
+        shell.file_name(bin_name)
+    }
+
+    /// Applies the options that are implemented by adjusting a copy of the `command`
+    #[cfg_attr(not(feature = "fig"), allow(unused_variables))]
+    pub(crate) fn prepare(&self, shell: Shell, command: &clap::Command) -> clap::Command {
+        let mut command = command.clone();
+        #[cfg(feature = "fig")]
+        if matches!(shell, Shell::Fig) && self.fig_long_about {
+            command = crate::fig::long_about(command);
+        }
+        if self.display_order {
+            command = sort_by_display_order(command);
+        }
+        if self.include_hidden {
+            command = unhide(command);
+        }
+        if self.value_names {
+            command = describe_value_names(command);
+        }
+        command
+    }
+
+    /// Applies the options that are implemented by adjusting the `script` generated from `command`
+    pub(crate) fn finish(&self, shell: Shell, command: &clap::Command, script: Vec<u8>) -> Vec<u8> {
+        #[cfg(feature = "fish")]
+        let script = if matches!(shell, Shell::Fish) && !self.fish_help_and_version {
+            crate::fish::without_help_and_version(command, &into_string(script)).into_bytes()
+        } else {
+            script
+        };
+
+        #[cfg(feature = "zsh")]
+        let script = if matches!(shell, Shell::Zsh) && self.zsh_form == ZshForm::Inline {
+            crate::zsh::inline(&into_string(script)).into_bytes()
+        } else {
+            script
+        };
+
+        #[cfg(feature = "bash")]
+        let script = if matches!(shell, Shell::Bash) && self.bash_form == BashForm::LazyLoad {
+            prefix_functions(&into_string(script), "comp_cmd").into_bytes()
+        } else {
+            script
+        };
+
+        #[cfg(feature = "elvish")]
+        let script = if matches!(shell, Shell::Elvish) && self.elvish_form == ElvishForm::Module {
+            crate::elvish::module(&into_string(script)).into_bytes()
+        } else {
+            script
+        };
+
+        #[cfg(any(feature = "bash", feature = "zsh"))]
+        let script = match &self.function_prefix {
+            Some(prefix) if defines_functions(shell) => {
+                prefix_functions(&into_string(script), prefix).into_bytes()
+            }
+            _ => script,
+        };
+
+        #[cfg(feature = "fig")]
+        let script = match (shell, &self.fig_icon) {
+            (Shell::Fig, Some(icon)) => {
+                crate::fig::with_icon(&into_string(script), icon).into_bytes()
+            }
+            _ => script,
+        };
+
+        #[cfg(feature = "nushell")]
+        let script = if matches!(shell, Shell::Nu) && self.nu_form == NuForm::Overlay {
+            crate::nu::overlay(&into_string(script)).into_bytes()
+        } else {
+            script
+        };
+
+        let script = if self.version_header {
+            let mut header = format!("generated by {}", crate::bin_name(command));
+            if let Some(version) = command.get_version() {
+                header.push_str(&format!(" {version}"));
+            }
+            crate::header::insert(shell, &into_string(script), &header).into_bytes()
+        } else {
+            script
+        };
+
+        // The byte order mark has to come before anything else, including the header
+        #[cfg(feature = "powershell")]
+        let script = if matches!(shell, Shell::PowerShell) && self.powershell_bom {
+            [UTF8_BOM.as_slice(), &script].concat()
+        } else {
+            script
+        };
+
+        script
+    }
+}
+
+#[cfg(feature = "powershell")]
+const UTF8_BOM: [u8; 3] = [0xEF, 0xBB, 0xBF];
+
+fn into_string(script: Vec<u8>) -> String {
+    String::from_utf8(script).expect("completions should be valid UTF-8")
+}
+
+/// Whether [`GenerateOptions::function_prefix()`] applies to `shell`'s completions
+#[cfg(any(feature = "bash", feature = "zsh"))]
+const fn defines_functions(shell: Shell) -> bool {
+    match shell {
+        #[cfg(feature = "bash")]
+        Shell::Bash => true,
+        #[cfg(feature = "zsh")]
+        Shell::Zsh => true,
+        #[allow(unreachable_patterns)]
+        _ => false,
+    }
+}
+
+/// Renames the first function `script` defines, e.g. `_cli`, and the functions named after it, like
+/// zsh's `_cli_commands`, to start with `_<prefix>_`
+#[cfg(any(feature = "bash", feature = "zsh"))]
+fn prefix_functions(script: &str, prefix: &str) -> String {
+    let Some(function) = script
+        .lines()
+        .find_map(|line| line.strip_suffix("() {"))
+        .filter(|function| function.starts_with('_'))
+    else {
+        return script.to_owned();
+    };
+    let is_name_char = |c: char| c.is_alphanumeric() || c == '-';
+
+    let mut prefixed = String::with_capacity(script.len());
+    let mut rest = script;
+    while let Some(index) = rest.find(function) {
+        let after = &rest[index + function.len()..];
+        let starts_word = !rest[..index]
+            .chars()
+            .next_back()
+            .is_some_and(|c| is_name_char(c) || c == '_');
+        let ends_word = !after.chars().next().is_some_and(is_name_char);
+
+        prefixed.push_str(&rest[..index]);
+        if starts_word && ends_word {
+            prefixed.push_str(&format!("_{prefix}{function}"));
+        } else {
+            prefixed.push_str(function);
+        }
+        rest = after;
+    }
+    prefixed.push_str(rest);
+    prefixed
+}
+
+fn unhide(command: clap::Command) -> clap::Command {
+    let aliases = command
+        .get_all_aliases()
+        .map(crate::leak)
+        .collect::<Vec<_>>();
+    let short_flag_aliases = command.get_all_short_flag_aliases().collect::<Vec<_>>();
+    let long_flag_aliases = command
+        .get_all_long_flag_aliases()
+        .map(crate::leak)
+        .collect::<Vec<_>>();
+    command
+        .hide(false)
+        .alias(None)
+        .visible_aliases(aliases)
+        .short_flag_alias(None)
+        .visible_short_flag_aliases(short_flag_aliases)
+        .long_flag_alias(None)
+        .visible_long_flag_aliases(long_flag_aliases)
+        .mut_args(unhide_arg)
+        .mut_subcommands(unhide)
+}
+
+fn unhide_arg(arg: clap::Arg) -> clap::Arg {
+    let aliases = arg.get_all_aliases().unwrap_or_default();
+    let aliases = aliases.into_iter().map(crate::leak).collect::<Vec<_>>();
+    let short_aliases = arg.get_all_short_aliases().unwrap_or_default();
+    let values = arg.get_possible_values();
+    let arg = arg
+        .hide(false)
+        .alias(None)
+        .visible_aliases(aliases)
+        .short_alias(None)
+        .visible_short_aliases(short_aliases);
+    if !values.iter().any(clap::builder::PossibleValue::is_hide_set) {
+        return arg;
+    }
+    let values = values.into_iter().map(|value| value.hide(false));
+    arg.value_parser(clap::builder::PossibleValuesParser::new(values))
+}
+
+fn describe_value_names(command: clap::Command) -> clap::Command {
+    command
+        .mut_args(|arg| {
+            let Some(value_names) = arg.get_value_names().filter(|_| !arg.is_positional()) else {
+                return arg;
+            };
+            let mut description = value_names
+                .iter()
+                .map(|value_name| format!("<{value_name}>"))
+                .collect::<Vec<_>>()
+                .join(" ");
+            if let Some(help) = arg.get_help() {
+                description.push_str(&format!(" {help}"));
+            }
+            arg.help(description)
+        })
+        .mut_subcommands(describe_value_names)
+}
+
+/// [`clap::Command::mut_arg()`] and [`clap::Command::mut_subcommand()`] move what they modify to
+/// the end, so visiting them in sorted order sorts them
+///
+/// Ties are broken like in `--help`, options by their short or long name and subcommands by their
+/// name.
+fn sort_by_display_order(command: clap::Command) -> clap::Command {
+    let mut options = command
+        .get_arguments()
+        .filter(|arg| !arg.is_positional())
+        .map(|arg| (option_sort_key(arg), arg.get_id().clone()))
+        .collect::<Vec<_>>();
+    options.sort();
+
+    let mut subcommands = command
+        .get_subcommands()
+        .map(|subcommand| {
+            (
+                subcommand.get_display_order(),
+                subcommand.get_name().to_owned(),
+            )
+        })
+        .collect::<Vec<_>>();
+    subcommands.sort();
+
+    let command = options
+        .into_iter()
+        .fold(command, |command, (_, id)| command.mut_arg(id, |arg| arg));
+    subcommands.into_iter().fold(command, |command, (_, name)| {
+        command.mut_subcommand(name, sort_by_display_order)
+    })
+}
+
+/// The key clap's `--help` sorts options by: `-a`, `-A`, `-b` and then options without a short
+/// name by their long name, then the others by their id
+fn option_sort_key(arg: &clap::Arg) -> (usize, String) {
+    let key = if let Some(short) = arg.get_short() {
+        let case = if short.is_ascii_lowercase() { '0' } else { '1' };
+        format!("{}{case}", short.to_ascii_lowercase())
+    } else if let Some(long) = arg.get_long() {
+        long.to_owned()
+    } else {
+        format!("{{{}", arg.get_id())
+    };
+    (arg.get_display_order(), key)
+}
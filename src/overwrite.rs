@@ -0,0 +1,15 @@
+//! What happens to existing completion files.
+
+/// What [`crate::Shell::generate_to_with_policy()`] does when the completion file already exists,
+/// e.g. one a user has customised.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OverwritePolicy {
+    /// The file is replaced, like [`crate::Shell::generate_to()`] does
+    #[default]
+    Overwrite,
+    /// The file is kept and nothing is written
+    SkipIfExists,
+    /// The file is kept and an [`std::io::ErrorKind::AlreadyExists`] error is returned
+    ErrorIfExists,
+}
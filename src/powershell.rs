@@ -0,0 +1,109 @@
+//! The differences between PowerShell's editions.
+
+use std::path::PathBuf;
+
+use crate::BaseDir;
+
+/// An edition of PowerShell, which [`crate::Shell::PowerShell`]'s completions support both of.
+///
+/// Only their profiles, where the completions are loaded from, differ, see
+/// [`PowerShellEdition::profile_path()`], [`PowerShellEdition::install()`] and
+/// [`PowerShellEdition::install_instructions()`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PowerShellEdition {
+    /// PowerShell 7 and later, `pwsh`, on any platform
+    #[default]
+    Core,
+    /// Windows PowerShell 5.1, `powershell.exe`, which comes with Windows
+    Windows,
+}
+
+impl PowerShellEdition {
+    /// The edition's profile for the current user and host, what `$PROFILE` is in its console, for
+    /// loading the completions as shown by [`crate::Shell::install_instructions()`].
+    ///
+    /// On Windows the profiles are under `Documents\PowerShell` and `Documents\WindowsPowerShell` in
+    /// the home directory, elsewhere PowerShell 7's is under `$XDG_CONFIG_HOME/powershell`.
+    ///
+    /// Returns [`None`] for Windows PowerShell on other platforms, or if the home directory is
+    /// unknown.
+    #[must_use]
+    pub fn profile_path(self) -> Option<PathBuf> {
+        self.profile_path_on(cfg!(windows))
+    }
+
+    /// Writes the completions next to the edition's [`Self::profile_path()`], creating its
+    /// directory if needed, and returns the path, for a `completions --install` flag.
+    ///
+    /// The profile isn't changed, it has to dot-source the completions, e.g. with
+    /// `. $PSScriptRoot\_cli.ps1`.
+    ///
+    /// # Errors
+    ///
+    /// [`std::io::ErrorKind::Unsupported`] for Windows PowerShell on other platforms,
+    /// [`std::io::ErrorKind::NotFound`] if the home directory is unknown, or see
+    /// [`crate::Shell::generate_to()`].
+    #[cfg(feature = "powershell")]
+    pub fn install(self, command: &mut clap::Command) -> Result<PathBuf, std::io::Error> {
+        self.install_on(command, cfg!(windows))
+    }
+
+    #[cfg(feature = "powershell")]
+    pub(crate) fn install_on(
+        self,
+        command: &mut clap::Command,
+        windows: bool,
+    ) -> Result<PathBuf, std::io::Error> {
+        if self == Self::Windows && !windows {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "Windows PowerShell only runs on Windows",
+            ));
+        }
+        let profile = self.profile_path_on(windows).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "the home directory is unknown",
+            )
+        })?;
+        let dir = profile.parent().expect("the profile is in a directory");
+        crate::Shell::PowerShell.generate_to(command, dir)
+    }
+
+    /// Short instructions for users on loading the completions from this edition's profile, like
+    /// [`crate::Shell::install_instructions()`] but also naming where the profile is.
+    #[cfg(feature = "powershell")]
+    #[must_use]
+    pub fn install_instructions(self, bin_name: &str) -> String {
+        let (name, profiles) = match self {
+            Self::Core => (
+                "PowerShell 7",
+                r"~\Documents\PowerShell\Microsoft.PowerShell_profile.ps1 on Windows
+~/.config/powershell/Microsoft.PowerShell_profile.ps1 elsewhere",
+            ),
+            Self::Windows => (
+                "Windows PowerShell",
+                r"~\Documents\WindowsPowerShell\Microsoft.PowerShell_profile.ps1",
+            ),
+        };
+        let (_, steps) = crate::Shell::PowerShell.setup_steps(bin_name);
+        format!(
+            "Add this to {name}'s profile, $PROFILE:\n\n{}\n\nThe profile is at:\n\n{}\n",
+            crate::indent(&steps),
+            crate::indent(profiles)
+        )
+    }
+
+    pub(crate) fn profile_path_on(self, windows: bool) -> Option<PathBuf> {
+        let dir = match (self, windows) {
+            (Self::Core, true) => crate::home_dir()?.join("Documents").join("PowerShell"),
+            (Self::Windows, true) => crate::home_dir()?
+                .join("Documents")
+                .join("WindowsPowerShell"),
+            (Self::Core, false) => BaseDir::Config.resolve()?.join("powershell"),
+            (Self::Windows, false) => return None,
+        };
+        Some(dir.join("Microsoft.PowerShell_profile.ps1"))
+    }
+}
@@ -0,0 +1,55 @@
+//! [`serde`] support for [`Shell`], using the names of its possible values.
+
+use clap::ValueEnum;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::Shell;
+
+/// Every shell's feature, by the name of its possible value, to explain why a shell which isn't
+/// in this build can't be deserialized
+pub const FEATURE_GATED: &[(&str, &str)] = &[
+    ("bash", "bash"),
+    ("carapace", "carapace"),
+    ("clink", "clink"),
+    ("elvish", "elvish"),
+    ("fig", "fig"),
+    ("fish", "fish"),
+    ("man", "man"),
+    ("murex", "murex"),
+    ("nushell", "nushell"),
+    ("powershell", "powershell"),
+    ("tcsh", "tcsh"),
+    ("xonsh", "xonsh"),
+    ("zsh", "zsh"),
+];
+
+impl Serialize for Shell {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.canonical_name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Shell {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        if let Ok(shell) = Self::from_str(&name, false) {
+            return Ok(shell);
+        }
+
+        match FEATURE_GATED.iter().find(|(shell, _)| *shell == name) {
+            Some((_, feature)) => Err(de::Error::custom(format!(
+                "the `{name}` shell needs clap_complete_command's `{feature}` feature"
+            ))),
+            None => Err(de::Error::custom(format!(
+                "unknown shell `{name}`, expected one of: {}",
+                Self::names().join(", ")
+            ))),
+        }
+    }
+}
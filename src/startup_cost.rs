@@ -0,0 +1,48 @@
+//! Rough estimates of how much completions slow down shell startup.
+
+/// Bytes of completions up to which they're [`StartupCost::Small`]
+const SMALL_BYTES: usize = 16 * 1024;
+/// Bytes of completions up to which they're [`StartupCost::Medium`]
+const MEDIUM_BYTES: usize = 128 * 1024;
+/// Subcommands and args up to which completions are [`StartupCost::Small`]
+const SMALL_ITEMS: usize = 100;
+/// Subcommands and args up to which completions are [`StartupCost::Medium`]
+const MEDIUM_ITEMS: usize = 1000;
+
+/// A rough classification of how much loading completions adds to shell startup, see
+/// [`crate::Shell::startup_cost_estimate()`].
+///
+/// Completions are classified by the largest of their size and their number of subcommands and
+/// args.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum StartupCost {
+    /// Up to 16 KiB and 100 subcommands and args, fine to load eagerly
+    Small,
+    /// Up to 128 KiB and 1000 subcommands and args, noticeable when loaded eagerly in slow shells
+    Medium,
+    /// Anything bigger, the completions should be lazy-loaded, e.g. installed where bash-completion
+    /// or zsh's `compinit` only load them when the command is first completed, rather than sourced
+    /// from the shell's startup file
+    Large,
+}
+
+/// Classifies completions of `bytes` bytes completing `items` subcommands and args
+pub const fn classify(bytes: usize, items: usize) -> StartupCost {
+    if bytes > MEDIUM_BYTES || items > MEDIUM_ITEMS {
+        StartupCost::Large
+    } else if bytes > SMALL_BYTES || items > SMALL_ITEMS {
+        StartupCost::Medium
+    } else {
+        StartupCost::Small
+    }
+}
+
+/// The number of subcommands and args of `command`, including its own
+pub fn count_items(command: &clap::Command) -> usize {
+    command.get_arguments().count()
+        + command
+            .get_subcommands()
+            .map(|subcommand| 1 + count_items(subcommand))
+            .sum::<usize>()
+}
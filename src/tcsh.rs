@@ -0,0 +1,105 @@
+//! A generator for tcsh, as no crate generates its completions.
+//!
+//! tcsh's `complete` builtin matches words by their position and the word before them, so long and
+//! short options are completed after `--` and `-`, values after the option or subcommand they
+//! belong to, and the subcommands or positional values of the command itself by position.
+
+use crate::walk;
+
+pub fn generate(command: &clap::Command, buffer: &mut dyn std::io::Write) {
+    let bin_name = command.get_bin_name().unwrap_or_else(|| command.get_name());
+
+    let (longs, shorts) = option_names(command);
+    let mut rules = Vec::new();
+    if !longs.is_empty() {
+        let longs = longs.iter().map(|long| &long[2..]).collect::<Vec<_>>();
+        rules.push(format!("c/--/({})/", longs.join(" ")));
+    }
+    if !shorts.is_empty() {
+        let shorts = shorts.iter().map(|short| &short[1..]).collect::<Vec<_>>();
+        rules.push(format!("c/-/({})/", shorts.join(" ")));
+    }
+    write_values(command, &mut rules);
+
+    let mut first = subcommand_names(command);
+    let mut positionals = walk::positionals(command).map(possible_values);
+    if let Some(values) = positionals.next() {
+        first.extend(values);
+    }
+    if !first.is_empty() {
+        rules.push(format!("p/1/({})/", first.join(" ")));
+    }
+    for (index, values) in positionals.enumerate() {
+        if !values.is_empty() {
+            rules.push(format!("p/{}/({})/", index + 2, values.join(" ")));
+        }
+    }
+
+    let mut script = format!("complete {bin_name}");
+    for rule in rules {
+        script.push_str(&format!(" \\\n    '{rule}'"));
+    }
+    script.push('\n');
+
+    buffer
+        .write_all(script.as_bytes())
+        .expect("failed to write completion file");
+}
+
+/// Adds the rules completing the values of `command`'s options and, recursively, the words
+/// following its subcommands
+fn write_values(command: &clap::Command, rules: &mut Vec<String>) {
+    for arg in walk::options(command).filter(|arg| arg.get_action().takes_values()) {
+        let values = possible_values(arg);
+        let list = if values.is_empty() {
+            "f".to_owned()
+        } else {
+            format!("({})", values.join(" "))
+        };
+        for name in walk::option_names(arg) {
+            rules.push(format!("n/{name}/{list}/"));
+        }
+    }
+
+    for subcommand in walk::subcommands(command) {
+        let (longs, shorts) = option_names(subcommand);
+        let mut words = subcommand_names(subcommand);
+        if let Some(arg) = walk::positionals(subcommand).next() {
+            words.extend(possible_values(arg));
+        }
+        words.extend(longs);
+        words.extend(shorts);
+        if !words.is_empty() {
+            for name in walk::subcommand_names(subcommand) {
+                rules.push(format!("n/{name}/({})/", words.join(" ")));
+            }
+        }
+
+        write_values(subcommand, rules);
+    }
+}
+
+/// The long and short names of `command`'s options, with their dashes
+fn option_names(command: &clap::Command) -> (Vec<String>, Vec<String>) {
+    walk::options(command)
+        .flat_map(walk::option_names)
+        .partition(|name| name.starts_with("--"))
+}
+
+/// The names and visible aliases of `command`'s subcommands
+fn subcommand_names(command: &clap::Command) -> Vec<String> {
+    walk::subcommands(command)
+        .flat_map(walk::subcommand_names)
+        .map(str::to_owned)
+        .collect()
+}
+
+/// The possible values of `arg` which can be listed in a `complete` rule
+fn possible_values(arg: &clap::Arg) -> Vec<String> {
+    walk::possible_values(arg)
+        .into_iter()
+        .filter(|value| {
+            !value.is_empty() && !value.contains(|c: char| c.is_whitespace() || "'/()".contains(c))
+        })
+        .collect()
+}
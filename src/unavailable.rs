@@ -0,0 +1,48 @@
+//! Converting [`clap_complete::Shell`]s which aren't in this build.
+
+/// Why a [`clap_complete::Shell`] couldn't be converted to a [`crate::Shell`]: its feature is
+/// disabled, or `clap_complete` added a shell this crate doesn't support yet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnavailableShellError {
+    shell: clap_complete::Shell,
+}
+
+impl UnavailableShellError {
+    pub(crate) const fn new(shell: clap_complete::Shell) -> Self {
+        Self { shell }
+    }
+
+    /// The shell which couldn't be converted
+    #[must_use]
+    pub const fn shell(&self) -> clap_complete::Shell {
+        self.shell
+    }
+
+    /// The feature which would enable the shell, or `None` if this crate doesn't support it
+    #[must_use]
+    pub const fn feature_name(&self) -> Option<&'static str> {
+        match self.shell {
+            clap_complete::Shell::Bash => Some("bash"),
+            clap_complete::Shell::Elvish => Some("elvish"),
+            clap_complete::Shell::Fish => Some("fish"),
+            clap_complete::Shell::PowerShell => Some("powershell"),
+            clap_complete::Shell::Zsh => Some("zsh"),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for UnavailableShellError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.feature_name() {
+            Some(feature) => write!(
+                f,
+                "the `{}` shell needs clap_complete_command's `{feature}` feature",
+                self.shell
+            ),
+            None => write!(f, "{} completions aren't supported yet", self.shell),
+        }
+    }
+}
+
+impl std::error::Error for UnavailableShellError {}
@@ -0,0 +1,128 @@
+//! Leaving hidden items out of commands.
+//!
+//! The generators of [`clap_complete`] and the other crates complete hidden subcommands, arguments
+//! and aliases like visible ones, or mark them as hidden in the fig and carapace specs, but clap
+//! has no way to remove them from a command. Instead a copy of the command is built from its
+//! visible parts for them.
+
+use clap::builder::Resettable;
+
+/// A copy of `command` without its hidden subcommands, arguments, aliases and possible values
+pub fn without_hidden(command: &clap::Command) -> clap::Command {
+    let mut copy = clap::Command::new(crate::leak(command.get_name()))
+        .visible_aliases(command.get_visible_aliases().map(crate::leak))
+        .visible_short_flag_aliases(command.get_visible_short_flag_aliases())
+        .visible_long_flag_aliases(command.get_visible_long_flag_aliases().map(crate::leak))
+        .display_order(command.get_display_order())
+        .hide(command.is_hide_set())
+        .flatten_help(command.is_flatten_help_set())
+        .no_binary_name(command.is_no_binary_name_set())
+        .dont_delimit_trailing_values(command.is_dont_delimit_trailing_values_set())
+        .disable_version_flag(command.is_disable_version_flag_set())
+        .propagate_version(command.is_propagate_version_set())
+        .next_line_help(command.is_next_line_help_set())
+        .disable_help_flag(command.is_disable_help_flag_set())
+        .disable_help_subcommand(command.is_disable_help_subcommand_set())
+        .disable_colored_help(command.is_disable_colored_help_set())
+        .dont_collapse_args_in_usage(command.is_dont_collapse_args_in_usage_set())
+        .arg_required_else_help(command.is_arg_required_else_help_set())
+        .allow_missing_positional(command.is_allow_missing_positional_set())
+        .subcommand_required(command.is_subcommand_required_set())
+        .allow_external_subcommands(command.is_allow_external_subcommands_set())
+        .args_conflicts_with_subcommands(command.is_args_conflicts_with_subcommands_set())
+        .args_override_self(command.is_args_override_self())
+        .subcommand_precedence_over_arg(command.is_subcommand_precedence_over_arg_set())
+        .subcommand_negates_reqs(command.is_subcommand_negates_reqs_set())
+        .multicall(command.is_multicall_set());
+
+    if let Some(bin_name) = command.get_bin_name() {
+        copy = copy.bin_name(bin_name.to_owned());
+    }
+    if let Some(display_name) = command.get_display_name() {
+        copy = copy.display_name(display_name.to_owned());
+    }
+    if let Some(short_flag) = command.get_short_flag() {
+        copy = copy.short_flag(short_flag);
+    }
+    if let Some(long_flag) = command.get_long_flag() {
+        copy = copy.long_flag(crate::leak(long_flag));
+    }
+    if let Some(version) = command.get_version() {
+        copy = copy.version(crate::leak(version));
+    }
+    if let Some(long_version) = command.get_long_version() {
+        copy = copy.long_version(crate::leak(long_version));
+    }
+    if let Some(author) = command.get_author() {
+        copy = copy.author(crate::leak(author));
+    }
+    if let Some(about) = command.get_about() {
+        copy = copy.about(about.clone());
+    }
+    if let Some(long_about) = command.get_long_about() {
+        copy = copy.long_about(long_about.clone());
+    }
+    if let Some(heading) = command.get_subcommand_help_heading() {
+        copy = copy.subcommand_help_heading(crate::leak(heading));
+    }
+    if let Some(value_name) = command.get_subcommand_value_name() {
+        copy = copy.subcommand_value_name(crate::leak(value_name));
+    }
+    if let Some(value_parser) = command.get_external_subcommand_value_parser() {
+        copy = copy.external_subcommand_value_parser(value_parser.clone());
+    }
+
+    let visible_args = command
+        .get_arguments()
+        .filter(|arg| !arg.is_hide_set())
+        .collect::<Vec<_>>();
+    let is_visible = |id: &clap::Id| visible_args.iter().any(|arg| arg.get_id() == id);
+
+    copy = copy.groups(command.get_groups().map(|group| {
+        let args = group.get_args().filter(|id| is_visible(id)).cloned();
+        group
+            .clone()
+            .arg(Resettable::Reset)
+            .args(args.collect::<Vec<_>>())
+    }));
+    copy = copy.args(visible_args.iter().map(|arg| {
+        let conflicts = command.get_arg_conflicts_with(arg);
+        let mut arg = without_hidden_aliases((*arg).clone());
+        if conflicts.iter().any(|conflict| conflict.is_hide_set()) {
+            arg = arg.conflicts_with(Resettable::Reset).conflicts_with_all(
+                conflicts
+                    .iter()
+                    .filter(|conflict| !conflict.is_hide_set())
+                    .map(|conflict| conflict.get_id().clone()),
+            );
+        }
+        without_hidden_values(arg)
+    }));
+
+    copy.subcommands(
+        command
+            .get_subcommands()
+            .filter(|subcommand| !subcommand.is_hide_set())
+            .map(without_hidden),
+    )
+}
+
+fn without_hidden_aliases(arg: clap::Arg) -> clap::Arg {
+    let aliases = arg.get_visible_aliases().unwrap_or_default();
+    let aliases = aliases.into_iter().map(crate::leak).collect::<Vec<_>>();
+    let short_aliases = arg.get_visible_short_aliases().unwrap_or_default();
+    arg.alias(None)
+        .visible_aliases(aliases)
+        .short_alias(None)
+        .visible_short_aliases(short_aliases)
+}
+
+/// `arg` completing only its visible possible values, if any are hidden
+fn without_hidden_values(arg: clap::Arg) -> clap::Arg {
+    let values = arg.get_possible_values();
+    if !values.iter().any(clap::builder::PossibleValue::is_hide_set) {
+        return arg;
+    }
+    let values = values.into_iter().filter(|value| !value.is_hide_set());
+    arg.value_parser(clap::builder::PossibleValuesParser::new(values))
+}
@@ -0,0 +1,64 @@
+//! What the hand-written generators complete of a command, so they agree on it and only differ in
+//! how they write it out.
+
+use clap::ValueHint;
+
+/// `command`'s options, without hidden ones
+pub fn options(command: &clap::Command) -> impl Iterator<Item = &clap::Arg> {
+    command
+        .get_arguments()
+        .filter(|arg| !arg.is_positional() && !arg.is_hide_set())
+}
+
+/// `command`'s positionals, without hidden ones
+pub fn positionals(command: &clap::Command) -> impl Iterator<Item = &clap::Arg> {
+    command.get_positionals().filter(|arg| !arg.is_hide_set())
+}
+
+/// `command`'s subcommands, without hidden ones
+pub fn subcommands(command: &clap::Command) -> impl Iterator<Item = &clap::Command> {
+    command
+        .get_subcommands()
+        .filter(|subcommand| !subcommand.is_hide_set())
+}
+
+/// The name and visible aliases of `subcommand`
+pub fn subcommand_names(subcommand: &clap::Command) -> impl Iterator<Item = &str> {
+    std::iter::once(subcommand.get_name()).chain(subcommand.get_visible_aliases())
+}
+
+/// The long names and then the short names of the option `arg`, with their visible aliases and
+/// dashes
+pub fn option_names(arg: &clap::Arg) -> Vec<String> {
+    arg.get_long_and_visible_aliases()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|long| format!("--{long}"))
+        .chain(
+            arg.get_short_and_visible_aliases()
+                .unwrap_or_default()
+                .into_iter()
+                .map(|short| format!("-{short}")),
+        )
+        .collect()
+}
+
+/// The possible values of `arg`, without hidden ones
+pub fn possible_values(arg: &clap::Arg) -> Vec<String> {
+    arg.get_possible_values()
+        .into_iter()
+        .filter(|value| !value.is_hide_set())
+        .map(|value| value.get_name().to_owned())
+        .collect()
+}
+
+/// Whether the values of `arg` are paths, as it has no possible values and its value hint is a
+/// path or unknown
+#[cfg_attr(not(any(feature = "clink", feature = "murex")), allow(dead_code))]
+pub fn completes_files(arg: &clap::Arg) -> bool {
+    possible_values(arg).is_empty()
+        && matches!(
+            arg.get_value_hint(),
+            ValueHint::Unknown | ValueHint::AnyPath | ValueHint::FilePath | ValueHint::DirPath
+        )
+}
@@ -0,0 +1,152 @@
+//! A generator for xonsh, as no crate generates its completions.
+//!
+//! The command's subcommands, options and possible values are written out as a Python dict which a
+//! contextual completer walks.
+
+use crate::walk;
+
+pub fn generate(command: &clap::Command, buffer: &mut dyn std::io::Write) {
+    let bin_name = command.get_bin_name().unwrap_or_else(|| command.get_name());
+    let function = format!(
+        "_{}_completer",
+        bin_name.replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+    );
+
+    let mut tree = String::new();
+    write_command(command, 0, &mut tree);
+
+    let script = format!(
+        r#"from xonsh.completers.completer import add_one_completer
+from xonsh.completers.tools import RichCompletion, contextual_command_completer_for
+
+{function}_command = {tree}
+
+
+@contextual_command_completer_for({bin})
+def {function}(context):
+    node = {function}_command
+    words = [arg.value for arg in context.args[1 : context.arg_index]]
+    for word in words:
+        node = node["subcommands"].get(word, node)
+
+    if words and words[-1] in node["options"]:
+        _, takes_value, values = node["options"][words[-1]]
+        if takes_value:
+            if not values:
+                return None
+            return {{RichCompletion(value) for value in values if value.startswith(context.prefix)}}
+
+    completions = {{
+        RichCompletion(name, description=subcommand["description"])
+        for name, subcommand in node["subcommands"].items()
+    }}
+    completions |= {{
+        RichCompletion(name, description=description)
+        for name, (description, _, _) in node["options"].items()
+    }}
+    completions |= {{RichCompletion(value) for value in node["values"]}}
+    return {{
+        completion for completion in completions if completion.startswith(context.prefix)
+    }}
+
+
+add_one_completer({bin}, {function}, "start")
+"#,
+        bin = python_string(bin_name),
+    );
+
+    buffer
+        .write_all(script.as_bytes())
+        .expect("failed to write completion file");
+}
+
+/// Writes `command` as a dict with its `description`, `options` (their description, whether they
+/// take a value, and their possible values), positionals' possible `values` and `subcommands`
+fn write_command(command: &clap::Command, depth: usize, tree: &mut String) {
+    let indent = "    ".repeat(depth + 1);
+    let description = command.get_about().map(ToString::to_string);
+
+    tree.push_str("{\n");
+    tree.push_str(&format!(
+        "{indent}\"description\": {},\n",
+        python_string(description.as_deref().unwrap_or_default())
+    ));
+
+    let mut options = Vec::new();
+    for arg in walk::options(command) {
+        let description = arg.get_help().map(ToString::to_string).unwrap_or_default();
+        let takes_value = if arg.get_action().takes_values() {
+            "True"
+        } else {
+            "False"
+        };
+        let values = python_list(&walk::possible_values(arg));
+        for name in walk::option_names(arg) {
+            options.push(format!(
+                "{}: ({}, {takes_value}, {values})",
+                python_string(&name),
+                python_string(&description),
+            ));
+        }
+    }
+    tree.push_str(&format!(
+        "{indent}\"options\": {},\n",
+        python_dict(&options, &indent)
+    ));
+
+    let values = walk::positionals(command)
+        .flat_map(walk::possible_values)
+        .collect::<Vec<_>>();
+    tree.push_str(&format!("{indent}\"values\": {},\n", python_list(&values)));
+
+    let mut subcommands = Vec::new();
+    for subcommand in walk::subcommands(command) {
+        let mut subtree = String::new();
+        write_command(subcommand, depth + 2, &mut subtree);
+        for name in walk::subcommand_names(subcommand) {
+            subcommands.push(format!("{}: {subtree}", python_string(name)));
+        }
+    }
+    tree.push_str(&format!(
+        "{indent}\"subcommands\": {},\n",
+        python_dict(&subcommands, &indent)
+    ));
+
+    tree.push_str(&format!("{}}}", "    ".repeat(depth)));
+}
+
+/// A dict of `items`, each on its own line indented one level more than `indent`
+fn python_dict(items: &[String], indent: &str) -> String {
+    if items.is_empty() {
+        return "{}".to_owned();
+    }
+    let items = items
+        .iter()
+        .map(|item| format!("{indent}    {item},\n"))
+        .collect::<String>();
+    format!("{{\n{items}{indent}}}")
+}
+
+fn python_list(values: &[String]) -> String {
+    let values = values
+        .iter()
+        .map(|value| python_string(value))
+        .collect::<Vec<_>>();
+    format!("[{}]", values.join(", "))
+}
+
+fn python_string(value: &str) -> String {
+    let mut string = String::from("\"");
+    for character in value.chars() {
+        match character {
+            '\\' => string.push_str("\\\\"),
+            '"' => string.push_str("\\\""),
+            '\n' => string.push_str("\\n"),
+            '\r' => string.push_str("\\r"),
+            '\t' => string.push_str("\\t"),
+            _ => string.push(character),
+        }
+    }
+    string.push('"');
+    string
+}
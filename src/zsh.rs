@@ -0,0 +1,142 @@
+//! Adjustments on top of [`clap_complete::Shell::Zsh`]'s output.
+
+use clap_complete::Generator;
+
+pub fn generate(command: &clap::Command, buffer: &mut dyn std::io::Write) {
+    let mut script = Vec::new();
+    clap_complete::Shell::Zsh.generate(&exclusive_conflicts(command.clone()), &mut script);
+    let script = String::from_utf8(script).expect("zsh completions should be valid UTF-8");
+
+    let script = require_equals(command, &script);
+
+    buffer
+        .write_all(script.as_bytes())
+        .expect("failed to write completion file");
+}
+
+/// `clap_complete` only encodes conflicts in `_arguments`' exclusion lists, so an
+/// [`clap::Arg::exclusive()`] option is made to conflict with every other option of its command,
+/// and the other way around.
+fn exclusive_conflicts(command: clap::Command) -> clap::Command {
+    let options = command
+        .get_arguments()
+        .filter(|arg| !arg.is_positional())
+        .map(|arg| (arg.get_id().clone(), arg.is_exclusive_set()))
+        .collect::<Vec<_>>();
+    if !options.iter().any(|(_, exclusive)| *exclusive) {
+        return command.mut_subcommands(exclusive_conflicts);
+    }
+
+    command
+        .mut_args(|arg| {
+            if arg.is_positional() {
+                return arg;
+            }
+            let conflicts = options
+                .iter()
+                .filter(|(id, exclusive)| {
+                    id != arg.get_id() && (arg.is_exclusive_set() || *exclusive)
+                })
+                .map(|(id, _)| id.clone())
+                .collect::<Vec<_>>();
+            arg.conflicts_with_all(conflicts)
+        })
+        .mut_subcommands(exclusive_conflicts)
+}
+
+/// `clap_complete` lets options take their value from the next word (`--color=` and `-c+`), so
+/// [`clap::Arg::require_equals()`] options are made to only take it after `=` (`--color=-` and
+/// `-c=-`).
+///
+/// Options are matched by their names, so a name is only changed when no option sharing it
+/// anywhere in the tree takes its value from the next word.
+fn require_equals(command: &clap::Command, script: &str) -> String {
+    let mut equals = Vec::new();
+    let mut separate = Vec::new();
+    collect_option_names(command, &mut equals, &mut separate);
+    equals.retain(|name| !separate.contains(name));
+
+    if equals.is_empty() {
+        return script.to_owned();
+    }
+
+    script
+        .split_inclusive('\n')
+        .map(|line| {
+            equals
+                .iter()
+                .find_map(|name| require_equals_in_line(line, name))
+                .unwrap_or_else(|| line.to_owned())
+        })
+        .collect()
+}
+
+fn collect_option_names(
+    command: &clap::Command,
+    equals: &mut Vec<String>,
+    separate: &mut Vec<String>,
+) {
+    for arg in command.get_opts() {
+        let names = arg
+            .get_short_and_visible_aliases()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|short| format!("-{short}"))
+            .chain(
+                arg.get_long_and_visible_aliases()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|long| format!("--{long}")),
+            );
+        if arg.is_require_equals_set() {
+            equals.extend(names);
+        } else {
+            separate.extend(names);
+        }
+    }
+
+    for subcommand in command.get_subcommands() {
+        collect_option_names(subcommand, equals, separate);
+    }
+}
+
+/// Replaces the separator after the option `name` in the `_arguments` spec `line`, if it's for
+/// `name`
+fn require_equals_in_line(line: &str, name: &str) -> Option<String> {
+    // '(conflicts)*--name=[help]...
+    let spec = line.strip_prefix('\'')?;
+    let spec = spec
+        .strip_prefix('(')
+        .and_then(|spec| spec.split_once(')'))
+        .map_or(spec, |(_, spec)| spec);
+    let spec = spec.strip_prefix('*').unwrap_or(spec);
+
+    let separator = if name.starts_with("--") { "=[" } else { "+[" };
+    let help = spec.strip_prefix(name)?.strip_prefix(separator)?;
+    let start = line.len() - help.len() - separator.len();
+    Some(format!("{}=-[{help}", &line[..start]))
+}
+
+/// Removes the `#compdef` line and calls `compdef` unconditionally, so the script can be sourced
+/// after `compinit`
+pub fn inline(script: &str) -> String {
+    let script = script
+        .strip_prefix("#compdef ")
+        .and_then(|script| script.split_once('\n'))
+        .map_or(script, |(_, script)| script);
+
+    let Some(start) = script.find("if [ \"$funcstack[1]\" = ") else {
+        return script.to_owned();
+    };
+    let Some((compdef, rest)) = script[start..]
+        .split_once("\nelse\n")
+        .and_then(|(_, rest)| rest.split_once('\n'))
+    else {
+        return script.to_owned();
+    };
+    let Some(end) = rest.strip_prefix("fi\n") else {
+        return script.to_owned();
+    };
+
+    format!("{}{}\n{end}", &script[..start], compdef.trim_start())
+}